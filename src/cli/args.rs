@@ -1,4 +1,5 @@
 use clap::{Parser, Subcommand};
+use std::path::PathBuf;
 
 #[derive(Parser, Debug)]
 #[command(name = "doum-cli")]
@@ -12,6 +13,27 @@ pub struct Cli {
     /// Auto mode: Automatically select mode based on input
     #[arg(value_name = "INPUT")]
     pub input: Option<String>,
+
+    /// Enable web search for this command
+    #[arg(long = "web", global = true, conflicts_with = "no_web")]
+    pub web: bool,
+
+    /// Disable web search for this command
+    #[arg(long = "no-web", global = true)]
+    pub no_web: bool,
+}
+
+impl Cli {
+    /// Per-invocation override of `llm.use_web_search`, if any
+    pub fn web_search_override(&self) -> Option<bool> {
+        if self.web {
+            Some(true)
+        } else if self.no_web {
+            Some(false)
+        } else {
+            None
+        }
+    }
 }
 
 #[derive(Subcommand, Debug)]
@@ -24,16 +46,72 @@ pub enum Commands {
     /// Secret management (API keys, tokens)
     Secret,
     /// Switch provider and model
-    Switch,
+    Switch {
+        /// Provider to switch to (interactive selection when omitted)
+        #[arg(requires = "model")]
+        provider: Option<String>,
+        /// Model to switch to
+        model: Option<String>,
+        /// Fetch the model list again instead of using the cached one
+        #[arg(long)]
+        refresh: bool,
+        /// Switch even if the model is not in the provider's model list
+        #[arg(long)]
+        force: bool,
+    },
     /// Ask questions (Ask mode)
     Ask {
         /// Question to ask
         question: String,
+        /// Attach an image (screenshot, diagram, ...); may be repeated
+        #[arg(long = "image", value_name = "FILE")]
+        images: Vec<PathBuf>,
+        /// Continue the most recent session
+        #[arg(long = "continue", short = 'c', conflicts_with = "session")]
+        continue_session: bool,
+        /// Continue the session with this id (or id prefix)
+        #[arg(long, value_name = "ID")]
+        session: Option<String>,
     },
     /// Suggest and execute commands (Suggest mode)
     Suggest {
         /// Request description
         request: String,
+        /// Execute the selected command (after confirmation) instead of showing the action menu
+        #[arg(long)]
+        exec: bool,
+    },
+    /// Multi-turn conversation with slash commands (Chat mode)
+    Chat,
+    /// Break down a shell command before running it (Explain mode)
+    Explain {
+        /// Command or one-liner to explain
+        command: String,
+    },
+    /// Diagnose a failed command and suggest a fix (Fix mode)
+    Fix {
+        /// Failed command (default: the last command recorded by the shell hook)
+        command: Option<String>,
+        /// Exit code of the failed command
+        #[arg(long)]
+        exit_code: Option<i32>,
+        /// Error output of the failed command (re-run after confirmation when missing)
+        #[arg(long)]
+        stderr: Option<String>,
+        /// Print the shell hook recording the last command (bash, zsh or fish)
+        #[arg(long, value_name = "SHELL", exclusive = true)]
+        hook: Option<String>,
+    },
+    /// Manage saved conversation sessions
+    Sessions {
+        #[command(subcommand)]
+        action: SessionsAction,
+    },
+    /// Show token usage and estimated cost
+    Usage {
+        /// Month to report (YYYY-MM, default: current month)
+        #[arg(long)]
+        month: Option<String>,
     },
 }
 
@@ -61,3 +139,30 @@ pub enum ConfigAction {
         key: String,
     },
 }
+
+#[derive(Subcommand, Debug)]
+pub enum SessionsAction {
+    /// List saved sessions, most recent first
+    List,
+    /// Show the messages of a session
+    Show {
+        /// Session id (or a unique prefix)
+        id: String,
+    },
+    /// Delete a session
+    Delete {
+        /// Session id (or a unique prefix)
+        id: String,
+    },
+    /// Export a session
+    Export {
+        /// Session id (or a unique prefix)
+        id: String,
+        /// Output format (md or json)
+        #[arg(long, default_value = "md")]
+        format: String,
+        /// Write to a file instead of stdout
+        #[arg(long, short, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+}
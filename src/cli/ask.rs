@@ -1,28 +1,132 @@
-use crate::llm::client::LLMRequest;
-use crate::llm::{LLMMessage, PromptBuilder, create_client};
-use crate::system::{get_system_info, load_config};
+use super::sessions::save_session;
+use crate::llm::client::{LLMMode, LLMRequest};
+use crate::llm::{
+    Client, LLMMessage, LLMResponse, PromptBuilder, StreamDelta, check_vision, create_client,
+    load_image,
+};
+use crate::system::{Config, Session, get_system_info};
 use anyhow::Result;
-use cliclack::spinner;
+use cliclack::{ProgressBar, spinner};
+use std::io::Write;
+use std::path::PathBuf;
 
-pub async fn handle_ask_command(question: &str) -> Result<()> {
-    let config = load_config()?;
-    let client = create_client(&config.llm)?;
+/// Answer a question, continuing `session` when given, and save the exchange
+pub async fn handle_ask_command(
+    config: &Config,
+    question: &str,
+    images: &[PathBuf],
+    session: Option<Session>,
+) -> Result<()> {
+    // Read the attachments before anything is sent, so a bad file fails fast
+    if !images.is_empty() {
+        check_vision(&config.llm)?;
+    }
+    let images = images
+        .iter()
+        .map(|path| load_image(path, &config.llm.provider))
+        .collect::<Result<Vec<_>>>()?;
+
+    let sp = spinner();
+    let client = create_client(&config.llm, &config.network)?
+        .budget(config.budget.clone())
+        .on_retry({
+            let sp = sp.clone();
+            move |notice| sp.set_message(format!("[ASK MODE] {}", notice))
+        });
     let system_info = get_system_info();
     let builder = PromptBuilder::new(system_info.clone());
 
-    let sp = spinner();
     sp.start("[ASK MODE] Waiting for answer...");
 
+    let mut session = session
+        .unwrap_or_else(|| Session::new(config.llm.provider.clone(), config.llm.model.clone()));
+    let message = LLMMessage::user_with_images(question, images);
+
     let request = LLMRequest {
+        mode: LLMMode::Ask,
         system: builder.build_ask(),
-        messages: vec![LLMMessage::user(question)],
+        // Images of earlier exchanges are only described, the answers already cover them
+        messages: session
+            .messages
+            .iter()
+            .cloned()
+            .map(LLMMessage::without_images)
+            .chain([message.clone()])
+            .collect(),
+        response_schema: None,
+        params: config.modes.for_mode(LLMMode::Ask),
     };
 
-    let response = client.generate(request).await?;
-
-    sp.stop("");
+    let response = stream_answer(&client, request, &sp).await?;
 
-    println!("\n{}\n", response);
+    session.provider = config.llm.provider.clone();
+    session.model = config.llm.model.clone();
+    session.messages.push(message);
+    session.messages.push(LLMMessage::assistant(response.text));
+    save_session(&mut session);
 
     Ok(())
 }
+
+/// Stream an answer to the terminal and return it once complete
+pub(crate) async fn stream_answer(
+    client: &Client,
+    request: LLMRequest,
+    sp: &ProgressBar,
+) -> Result<LLMResponse> {
+    // Print deltas as they arrive, stopping the spinner on the first one.
+    // Reasoning (when thinking is enabled) is shown before the answer.
+    let mut started = false;
+    let mut in_reasoning = false;
+    let result = client
+        .generate_stream(request, &mut |delta| {
+            if !started {
+                sp.stop("");
+                println!();
+                if let Some(fallback) = client.fallback_in_use() {
+                    println!("↪ Answered by fallback {}\n", fallback);
+                }
+                started = true;
+            }
+
+            match delta {
+                StreamDelta::Reasoning(text) => {
+                    if !in_reasoning {
+                        println!("💭 Reasoning\n");
+                        in_reasoning = true;
+                    }
+                    print!("{}", text);
+                }
+                StreamDelta::Text(text) => {
+                    if in_reasoning {
+                        println!("\n\n💬 Answer\n");
+                        in_reasoning = false;
+                    }
+                    print!("{}", text);
+                }
+            }
+            let _ = std::io::stdout().flush();
+        })
+        .await;
+
+    if !started {
+        sp.stop("");
+    }
+    let response = result?;
+
+    println!("\n");
+
+    // List the web sources cited by the answer
+    if !response.citations.is_empty() {
+        println!("🔗 Sources\n");
+        for (i, citation) in response.citations.iter().enumerate() {
+            match citation.title {
+                Some(ref title) => println!("  [{}] {} - {}", i + 1, title, citation.url),
+                None => println!("  [{}] {}", i + 1, citation.url),
+            }
+        }
+        println!();
+    }
+
+    Ok(response)
+}
@@ -1,37 +1,54 @@
 use super::ask::handle_ask_command;
 use super::suggest::handle_suggest_command;
-use crate::llm::client::LLMRequest;
-use crate::llm::{LLMMessage, PromptBuilder, create_client, parse_auto_mode};
-use crate::system::{get_system_info, load_config};
+use crate::llm::client::{LLMMode, LLMRequest};
+use crate::llm::{
+    AutoResponse, LLMMessage, PromptBuilder, ResponseSchema, create_client, parse_auto_mode,
+};
+use crate::system::{Config, get_system_info};
 use anyhow::Result;
 use cliclack::spinner;
 
-pub async fn handle_auto_command(input: &str) -> Result<()> {
-    let config = load_config()?;
-    let client = create_client(&config.llm)?;
+/// Select a mode for the input and run it. Returns the exit code of an executed command.
+pub async fn handle_auto_command(config: &Config, input: &str) -> Result<i32> {
+    let sp = spinner();
+    let client = create_client(&config.llm, &config.network)?
+        .budget(config.budget.clone())
+        .on_retry({
+            let sp = sp.clone();
+            move |notice| sp.set_message(format!("[AUTO MODE] {}", notice))
+        });
     let system_info = get_system_info();
     let builder = PromptBuilder::new(system_info.clone());
 
-    let sp = spinner();
     sp.start("[AUTO MODE] Selecting mode...");
 
     let llm_request = LLMRequest {
+        mode: LLMMode::Auto,
         system: builder.build_auto_mode(),
         messages: vec![LLMMessage::user(input)],
+        response_schema: Some(ResponseSchema::of::<AutoResponse>("auto_response")),
+        params: config.modes.for_mode(LLMMode::Auto),
     };
 
     let mode_response = client.generate_with_parser(llm_request, parse_auto_mode).await?;
 
-    sp.stop("");
+    match client.fallback_in_use() {
+        Some(fallback) => sp.stop(format!("[AUTO MODE] Answered by fallback {}", fallback)),
+        None => sp.stop(""),
+    }
 
     // Execute based on selected mode
     match mode_response.mode.as_str() {
-        "ask" => handle_ask_command(input).await,
-        "suggest" => handle_suggest_command(input).await,
+        "ask" => handle_ask_command(config, input, &[], None)
+            .await
+            .map(|_| 0),
+        "suggest" => handle_suggest_command(config, input, false).await,
         unknown => {
             println!("⚠️  Unknown mode: {}", unknown);
             println!("💡 Falling back to Ask mode.\n");
-            handle_ask_command(input).await
+            handle_ask_command(config, input, &[], None)
+                .await
+                .map(|_| 0)
         }
     }
 }
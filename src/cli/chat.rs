@@ -0,0 +1,325 @@
+use super::ask::stream_answer;
+use super::suggest::select_suggestion;
+use crate::llm::client::{LLMMode, LLMRequest};
+use crate::llm::{
+    Client, LLMError, LLMMessage, LLMRole, PromptBuilder, Provider, ResponseSchema,
+    SuggestResponse, SystemPrompt, create_client, find_context_window, parse_suggest,
+};
+use crate::system::{Config, SystemInfo, get_system_info, messages_to_markdown};
+use crate::tools::execute_command;
+use anyhow::{Context, Result};
+use cliclack::{ProgressBar, spinner};
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use time::OffsetDateTime;
+
+/// Context window assumed for models without a preset
+const DEFAULT_CONTEXT_WINDOW: u64 = 128_000;
+
+/// Ollama serves a small context unless `num_ctx` is raised
+const OLLAMA_CONTEXT_WINDOW: u64 = 8_192;
+
+/// Longest command output kept in the history after `/exec`, in characters
+const MAX_EXEC_OUTPUT: usize = 8_000;
+
+const HELP: &str = "\
+Commands:
+  /suggest <task>   Suggest commands for a task, using the conversation so far
+  /exec <command>   Run a command and add its output to the conversation
+  /model [name]     Show the model, or switch to another model of the provider
+  /clear            Forget the conversation
+  /save [file]      Save the conversation as Markdown
+  /help             Show this help
+  /exit             Quit (or Ctrl+D)";
+
+/// Multi-turn conversation kept in memory until the REPL exits
+struct Chat {
+    config: Config,
+    system_info: SystemInfo,
+    builder: PromptBuilder,
+    history: Vec<LLMMessage>,
+}
+
+pub async fn handle_chat_command(config: &Config) -> Result<()> {
+    let system_info = get_system_info();
+    let mut chat = Chat {
+        config: config.clone(),
+        builder: PromptBuilder::new(system_info.clone()),
+        system_info,
+        history: Vec::new(),
+    };
+
+    cliclack::intro("💬 doum chat")?;
+    println!(
+        "Chatting with {}/{}. Type /help for commands, /exit or Ctrl+D to quit.",
+        chat.config.llm.provider.as_str(),
+        chat.config.llm.model
+    );
+
+    while let Some(line) = chat.read_line()? {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let Some(command) = line.strip_prefix('/') else {
+            chat.answer(line).await;
+            continue;
+        };
+
+        let (name, arg) = match command.split_once(char::is_whitespace) {
+            Some((name, arg)) => (name, arg.trim()),
+            None => (command, ""),
+        };
+        match (name, arg) {
+            ("exit" | "quit", _) => break,
+            ("help", _) => println!("{}", HELP),
+            ("clear", _) => {
+                chat.history.clear();
+                println!("🧹 Conversation cleared");
+            }
+            ("model", "") => println!(
+                "🤖 {}/{} ({} token context window)",
+                chat.config.llm.provider.as_str(),
+                chat.config.llm.model,
+                chat.context_window()
+            ),
+            ("model", model) => {
+                chat.config.llm.model = model.to_string();
+                println!(
+                    "🔄 Switched to {}/{} for this chat",
+                    chat.config.llm.provider.as_str(),
+                    model
+                );
+            }
+            ("suggest", "") => println!("Usage: /suggest <task>"),
+            ("suggest", task) => {
+                if let Err(e) = chat.suggest(task).await {
+                    print_error(&e);
+                }
+            }
+            ("exec", "") => println!("Usage: /exec <command>"),
+            ("exec", command) => {
+                if let Err(e) = chat.exec(command) {
+                    print_error(&e);
+                }
+            }
+            ("save", path) => match chat.save(path) {
+                Ok(path) => println!("💾 Saved to {}", path.display()),
+                Err(e) => print_error(&e),
+            },
+            _ => println!("Unknown command: /{}. Type /help for commands", name),
+        }
+    }
+
+    cliclack::outro("👋 Bye")?;
+    Ok(())
+}
+
+impl Chat {
+    /// Read the next line, `None` at end of input
+    fn read_line(&mut self) -> Result<Option<String>> {
+        print!("\n› ");
+        std::io::stdout().flush()?;
+
+        let mut line = String::new();
+        if std::io::stdin().read_line(&mut line)? == 0 {
+            println!();
+            return Ok(None);
+        }
+        Ok(Some(line))
+    }
+
+    /// Send a message and stream the answer, keeping both in the history
+    async fn answer(&mut self, message: &str) {
+        let saved = self.history.clone();
+        self.push_user(message);
+
+        let result = async {
+            let (client, sp) = self.client("Waiting for answer...")?;
+            let request = self.request(LLMMode::Chat, self.builder.build_ask(), None);
+            stream_answer(&client, request, &sp).await
+        }
+        .await;
+
+        match result {
+            Ok(response) => self.history.push(LLMMessage::assistant(response.text)),
+            Err(e) => {
+                // Forget the unanswered message so it can be sent again
+                self.history = saved;
+                print_error(&e);
+            }
+        }
+    }
+
+    /// Suggest commands with the conversation as context, then copy or execute one
+    async fn suggest(&mut self, task: &str) -> Result<()> {
+        let saved = self.history.clone();
+        self.push_user(task);
+
+        let result = async {
+            let (client, sp) = self.client("Generating commands...")?;
+            let request = self.request(
+                LLMMode::Suggest,
+                self.builder.build_suggest(),
+                Some(ResponseSchema::of::<SuggestResponse>("suggest_response")),
+            );
+            let response = client.generate_with_parser(request, parse_suggest).await;
+            sp.stop("");
+            response
+        }
+        .await;
+
+        let response = match result {
+            Ok(response) => response,
+            Err(e) => {
+                self.history = saved;
+                return Err(e);
+            }
+        };
+
+        self.history
+            .push(LLMMessage::assistant(response.to_message()));
+
+        select_suggestion(&response, &self.system_info, false)?;
+        Ok(())
+    }
+
+    /// Run a command and add its output to the conversation
+    fn exec(&mut self, command: &str) -> Result<()> {
+        let output = execute_command(command, &self.system_info, None)?;
+        tracing::info!(
+            "Executed command `{}` with exit code {}",
+            command,
+            output.exit_code
+        );
+        let mut display = output.display();
+        if display.is_empty() {
+            display = "(no output)\n".to_string();
+        }
+        print!("{}", display);
+
+        if display.len() > MAX_EXEC_OUTPUT {
+            let mut start = display.len() - MAX_EXEC_OUTPUT;
+            while !display.is_char_boundary(start) {
+                start += 1;
+            }
+            display = format!("...\n{}", &display[start..]);
+        }
+        self.push_user(&format!(
+            "I ran `{}` (exit code {}):\n{}",
+            command,
+            output.exit_code,
+            display.trim_end()
+        ));
+
+        Ok(())
+    }
+
+    /// Write the conversation as Markdown, to `path` or a timestamped file in the current directory
+    fn save(&self, path: &str) -> Result<PathBuf> {
+        let path = if path.is_empty() {
+            let now = OffsetDateTime::now_utc();
+            PathBuf::from(format!(
+                "doum-chat-{:04}{:02}{:02}-{:02}{:02}{:02}.md",
+                now.year(),
+                now.month() as u8,
+                now.day(),
+                now.hour(),
+                now.minute(),
+                now.second()
+            ))
+        } else {
+            PathBuf::from(path)
+        };
+
+        let mut content = format!(
+            "# doum chat\n\nModel: {}/{}\n",
+            self.config.llm.provider.as_str(),
+            self.config.llm.model
+        );
+        content.push_str(&messages_to_markdown(&self.history));
+
+        fs::write(&path, content).with_context(|| format!("Failed to write {}", path.display()))?;
+        Ok(path)
+    }
+
+    /// Add a user message, merging it into a preceding one so roles keep alternating
+    fn push_user(&mut self, text: &str) {
+        match self.history.last_mut() {
+            Some(last) if last.role == LLMRole::User => {
+                *last = LLMMessage::user(format!("{}\n\n{}", last.text(), text));
+            }
+            _ => self.history.push(LLMMessage::user(text)),
+        }
+    }
+
+    /// Request with the whole history, dropping the oldest exchanges once it
+    /// fills three quarters of the context window (the rest is left for the answer)
+    fn request(
+        &mut self,
+        mode: LLMMode,
+        system: SystemPrompt,
+        response_schema: Option<ResponseSchema>,
+    ) -> LLMRequest {
+        let limit = self.context_window() / 4 * 3;
+        let mut request = LLMRequest {
+            mode,
+            system,
+            messages: self.history.clone(),
+            response_schema,
+            params: self.config.modes.for_mode(mode),
+        };
+
+        let mut dropped = 0;
+        while request.messages.len() > 1 && request.estimated_input_tokens() > limit {
+            // Drop a whole exchange, so the history still starts with a user message
+            let count = match request.messages.get(1) {
+                Some(next) if next.role == LLMRole::Assistant && request.messages.len() > 2 => 2,
+                _ => 1,
+            };
+            request.messages.drain(..count);
+            dropped += count;
+        }
+        if dropped > 0 {
+            self.history.drain(..dropped);
+            println!(
+                "✂️  Dropped the {} oldest messages to fit the context window",
+                dropped
+            );
+        }
+
+        request
+    }
+
+    /// Context window of the current model, from the presets
+    fn context_window(&self) -> u64 {
+        let llm = &self.config.llm;
+        find_context_window(&llm.provider, &llm.model).unwrap_or(match llm.provider {
+            Provider::Ollama => OLLAMA_CONTEXT_WINDOW,
+            _ => DEFAULT_CONTEXT_WINDOW,
+        })
+    }
+
+    /// Client for the current model, with a started spinner showing retries
+    fn client(&self, message: &str) -> Result<(Client, ProgressBar)> {
+        let sp = spinner();
+        let client = create_client(&self.config.llm, &self.config.network)?
+            .budget(self.config.budget.clone())
+            .on_retry({
+                let sp = sp.clone();
+                move |notice| sp.set_message(format!("[CHAT] {}", notice))
+            });
+        sp.start(format!("[CHAT] {}", message));
+        Ok((client, sp))
+    }
+}
+
+/// Report a failed turn without leaving the chat
+fn print_error(e: &anyhow::Error) {
+    eprintln!("\n[Error] {}\n", e);
+    if let Some(llm_error) = e.chain().find_map(|c| c.downcast_ref::<LLMError>()) {
+        eprintln!("💡 {}", llm_error.hint());
+    }
+}
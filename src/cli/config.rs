@@ -1,5 +1,6 @@
 use crate::cli::args::ConfigAction;
-use crate::system::{get_config_path, load_config, load_default_config, save_config};
+use crate::llm::{GenerationParams, Provider};
+use crate::system::{ModesConfig, get_config_path, load_config, load_default_config, save_config};
 use anyhow::Result;
 use std::{fmt::Display, str::FromStr};
 
@@ -41,7 +42,12 @@ pub fn handle_config_command(action: Option<ConfigAction>) -> Result<()> {
 }
 
 fn get_value(key: &str) -> Result<String> {
-    let config = load_config()?;
+    let mut config = load_config()?;
+
+    if let Some((mode, field)) = split_mode_key(key) {
+        let params = *mode_params(&mut config.modes, mode, key)?;
+        return get_mode_value(&params, field, key);
+    }
 
     let value = match key {
         "llm.provider" => config.llm.provider.as_str().to_string(),
@@ -49,10 +55,39 @@ fn get_value(key: &str) -> Result<String> {
         "llm.timeout" => config.llm.timeout.to_string(),
         "llm.use_thinking" => config.llm.use_thinking.to_string(),
         "llm.use_web_search" => config.llm.use_web_search.to_string(),
+        "llm.max_retries" => config.llm.max_retries.to_string(),
+        "llm.ollama_url" => config.llm.ollama_url,
+        "llm.replay.mode" => config.llm.replay.mode.as_str().to_string(),
+        "llm.replay.dir" => {
+            optional_to_string(config.llm.replay.dir.map(|d| d.display().to_string()))
+        }
         "context.max_lines" => config.context.max_lines.to_string(),
         "context.max_size_kb" => config.context.max_size_kb.to_string(),
         "logging.enabled" => config.logging.enabled.to_string(),
         "logging.level" => config.logging.level,
+        "budget.daily_usd" => optional_to_string(config.budget.daily_usd),
+        "budget.monthly_usd" => optional_to_string(config.budget.monthly_usd),
+        "budget.warn_input_tokens" => config.budget.warn_input_tokens.to_string(),
+        "budget.default_input_price" => optional_to_string(config.budget.default_input_price),
+        "budget.default_output_price" => optional_to_string(config.budget.default_output_price),
+        "network.proxy" => optional_to_string(config.network.proxy),
+        "network.no_proxy" => optional_to_string(config.network.no_proxy),
+        "network.ca_certs" => {
+            let paths: Vec<_> = config
+                .network
+                .ca_certs
+                .iter()
+                .map(|p| p.display().to_string())
+                .collect();
+            paths.join(",")
+        }
+        "network.client_cert" => {
+            optional_to_string(config.network.client_cert.map(|p| p.display().to_string()))
+        }
+        "network.client_key" => {
+            optional_to_string(config.network.client_key.map(|p| p.display().to_string()))
+        }
+        "network.connect_timeout" => optional_to_string(config.network.connect_timeout),
         _ => anyhow::bail!("Unknown config key: {}", key),
     };
 
@@ -62,9 +97,19 @@ fn get_value(key: &str) -> Result<String> {
 fn set_value(key: &str, value: &str) -> Result<()> {
     let mut config = load_config()?;
 
+    if let Some((mode, field)) = split_mode_key(key) {
+        set_mode_value(
+            mode_params(&mut config.modes, mode, key)?,
+            field,
+            value,
+            key,
+        )?;
+        return save_config(&config);
+    }
+
     match key {
         "llm.provider" => {
-            config.llm.provider = value.parse()?;
+            config.llm.provider = Provider::parse_available(value, &config.llm)?;
         }
         "llm.model" => {
             config.llm.model = value.to_string();
@@ -78,6 +123,18 @@ fn set_value(key: &str, value: &str) -> Result<()> {
         "llm.use_web_search" => {
             config.llm.use_web_search = parse_value(value, "use_web_search")?;
         }
+        "llm.max_retries" => {
+            config.llm.max_retries = parse_value(value, "max_retries")?;
+        }
+        "llm.ollama_url" => {
+            config.llm.ollama_url = value.to_string();
+        }
+        "llm.replay.mode" => {
+            config.llm.replay.mode = value.parse()?;
+        }
+        "llm.replay.dir" => {
+            config.llm.replay.dir = Some(value.into());
+        }
         "context.max_lines" => {
             config.context.max_lines = parse_value(value, "max_lines")?;
         }
@@ -90,6 +147,45 @@ fn set_value(key: &str, value: &str) -> Result<()> {
         "logging.level" => {
             config.logging.level = value.to_string();
         }
+        "budget.daily_usd" => {
+            config.budget.daily_usd = Some(parse_usd(value, "daily_usd")?);
+        }
+        "budget.monthly_usd" => {
+            config.budget.monthly_usd = Some(parse_usd(value, "monthly_usd")?);
+        }
+        "budget.warn_input_tokens" => {
+            config.budget.warn_input_tokens = parse_value(value, "warn_input_tokens")?;
+        }
+        "budget.default_input_price" => {
+            config.budget.default_input_price = Some(parse_usd(value, "default_input_price")?);
+        }
+        "budget.default_output_price" => {
+            config.budget.default_output_price = Some(parse_usd(value, "default_output_price")?);
+        }
+        "network.proxy" => {
+            config.network.proxy = Some(value.to_string());
+        }
+        "network.no_proxy" => {
+            config.network.no_proxy = Some(value.to_string());
+        }
+        "network.ca_certs" => {
+            // Comma-separated list of PEM files
+            config.network.ca_certs = value
+                .split(',')
+                .map(str::trim)
+                .filter(|p| !p.is_empty())
+                .map(Into::into)
+                .collect();
+        }
+        "network.client_cert" => {
+            config.network.client_cert = Some(value.into());
+        }
+        "network.client_key" => {
+            config.network.client_key = Some(value.into());
+        }
+        "network.connect_timeout" => {
+            config.network.connect_timeout = Some(parse_value(value, "connect_timeout")?);
+        }
         _ => anyhow::bail!("Unknown config key: {}", key),
     }
 
@@ -101,16 +197,45 @@ fn unset_value(key: &str) -> Result<()> {
     let default_config = load_default_config()?;
     let mut config = load_config()?;
 
+    if let Some((mode, field)) = split_mode_key(key) {
+        // Every mode setting is unset by default
+        set_mode_value(mode_params(&mut config.modes, mode, key)?, field, "", key)?;
+        return save_config(&config);
+    }
+
     match key {
         "llm.provider" => config.llm.provider = default_config.llm.provider,
         "llm.model" => config.llm.model = default_config.llm.model,
         "llm.timeout" => config.llm.timeout = default_config.llm.timeout,
         "llm.use_thinking" => config.llm.use_thinking = default_config.llm.use_thinking,
         "llm.use_web_search" => config.llm.use_web_search = default_config.llm.use_web_search,
+        "llm.max_retries" => config.llm.max_retries = default_config.llm.max_retries,
+        "llm.ollama_url" => config.llm.ollama_url = default_config.llm.ollama_url,
+        "llm.replay.mode" => config.llm.replay.mode = default_config.llm.replay.mode,
+        "llm.replay.dir" => config.llm.replay.dir = default_config.llm.replay.dir,
         "context.max_lines" => config.context.max_lines = default_config.context.max_lines,
         "context.max_size_kb" => config.context.max_size_kb = default_config.context.max_size_kb,
         "logging.enabled" => config.logging.enabled = default_config.logging.enabled,
         "logging.level" => config.logging.level = default_config.logging.level,
+        "budget.daily_usd" => config.budget.daily_usd = default_config.budget.daily_usd,
+        "budget.monthly_usd" => config.budget.monthly_usd = default_config.budget.monthly_usd,
+        "budget.warn_input_tokens" => {
+            config.budget.warn_input_tokens = default_config.budget.warn_input_tokens
+        }
+        "budget.default_input_price" => {
+            config.budget.default_input_price = default_config.budget.default_input_price
+        }
+        "budget.default_output_price" => {
+            config.budget.default_output_price = default_config.budget.default_output_price
+        }
+        "network.proxy" => config.network.proxy = default_config.network.proxy,
+        "network.no_proxy" => config.network.no_proxy = default_config.network.no_proxy,
+        "network.ca_certs" => config.network.ca_certs = default_config.network.ca_certs,
+        "network.client_cert" => config.network.client_cert = default_config.network.client_cert,
+        "network.client_key" => config.network.client_key = default_config.network.client_key,
+        "network.connect_timeout" => {
+            config.network.connect_timeout = default_config.network.connect_timeout
+        }
         _ => anyhow::bail!("Unknown config key: {}", key),
     }
 
@@ -126,3 +251,102 @@ where
         .parse()
         .map_err(|e| anyhow::anyhow!("Invalid {} value: {} - {}", field_name, value, e))
 }
+
+/// Parse a spending limit in USD
+fn parse_usd(value: &str, field_name: &str) -> Result<f64> {
+    let usd: f64 = parse_value(value, field_name)?;
+    if !usd.is_finite() || usd < 0.0 {
+        anyhow::bail!(
+            "Invalid {} value: {} - must be a non-negative amount",
+            field_name,
+            value
+        );
+    }
+    Ok(usd)
+}
+
+/// Display an optional value, or `none` when unset
+fn optional_to_string<T: Display>(value: Option<T>) -> String {
+    value.map_or_else(|| "none".to_string(), |v| v.to_string())
+}
+
+/// Split a `modes.<mode>.<field>` key
+fn split_mode_key(key: &str) -> Option<(&str, &str)> {
+    key.strip_prefix("modes.")?.split_once('.')
+}
+
+fn mode_params<'a>(
+    modes: &'a mut ModesConfig,
+    mode: &str,
+    key: &str,
+) -> Result<&'a mut GenerationParams> {
+    match mode {
+        "ask" => Ok(&mut modes.ask),
+        "suggest" => Ok(&mut modes.suggest),
+        "auto" => Ok(&mut modes.auto),
+        "explain" => Ok(&mut modes.explain),
+        "fix" => Ok(&mut modes.fix),
+        "chat" => Ok(&mut modes.chat),
+        _ => anyhow::bail!("Unknown config key: {}", key),
+    }
+}
+
+fn get_mode_value(params: &GenerationParams, field: &str, key: &str) -> Result<String> {
+    let value = match field {
+        "max_tokens" => optional_to_string(params.max_tokens),
+        "temperature" => optional_to_string(params.temperature),
+        "top_p" => optional_to_string(params.top_p),
+        "timeout" => optional_to_string(params.timeout),
+        "reasoning_effort" => optional_to_string(params.reasoning_effort.map(|e| e.as_str())),
+        _ => anyhow::bail!("Unknown config key: {}", key),
+    };
+
+    Ok(value)
+}
+
+/// Set a mode field, or unset it when `value` is empty
+fn set_mode_value(
+    params: &mut GenerationParams,
+    field: &str,
+    value: &str,
+    key: &str,
+) -> Result<()> {
+    let value = Some(value).filter(|v| !v.is_empty());
+
+    match field {
+        "max_tokens" => {
+            params.max_tokens = value.map(|v| parse_value(v, "max_tokens")).transpose()?;
+        }
+        "temperature" => {
+            params.temperature = value
+                .map(|v| parse_range(v, "temperature", 2.0))
+                .transpose()?;
+        }
+        "top_p" => {
+            params.top_p = value.map(|v| parse_range(v, "top_p", 1.0)).transpose()?;
+        }
+        "timeout" => {
+            params.timeout = value.map(|v| parse_value(v, "timeout")).transpose()?;
+        }
+        "reasoning_effort" => {
+            params.reasoning_effort = value.map(str::parse).transpose()?;
+        }
+        _ => anyhow::bail!("Unknown config key: {}", key),
+    }
+
+    Ok(())
+}
+
+/// Parse a sampling parameter between 0 and `max`
+fn parse_range(value: &str, field_name: &str, max: f64) -> Result<f64> {
+    let number: f64 = parse_value(value, field_name)?;
+    if !(0.0..=max).contains(&number) {
+        anyhow::bail!(
+            "Invalid {} value: {} - must be between 0 and {}",
+            field_name,
+            value,
+            max
+        );
+    }
+    Ok(number)
+}
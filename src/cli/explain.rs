@@ -0,0 +1,115 @@
+use crate::llm::client::{LLMMode, LLMRequest};
+use crate::llm::{
+    ExplainResponse, LLMMessage, PipelineStage, PromptBuilder, ResponseSchema, create_client,
+    parse_explain,
+};
+use crate::system::{Config, get_system_info};
+use anyhow::Result;
+use cliclack::spinner;
+
+/// Widest part text used to align the kind column; longer parts are not padded
+const MAX_PART_WIDTH: usize = 24;
+
+pub async fn handle_explain_command(config: &Config, command: &str) -> Result<()> {
+    let sp = spinner();
+    let client = create_client(&config.llm, &config.network)?
+        .budget(config.budget.clone())
+        .on_retry({
+            let sp = sp.clone();
+            move |notice| sp.set_message(format!("[EXPLAIN MODE] {}", notice))
+        });
+    let system_info = get_system_info();
+    let builder = PromptBuilder::new(system_info);
+
+    sp.start("[EXPLAIN MODE] Breaking down the command...");
+
+    let llm_request = LLMRequest {
+        mode: LLMMode::Explain,
+        system: builder.build_explain(),
+        messages: vec![LLMMessage::user(command)],
+        response_schema: Some(ResponseSchema::of::<ExplainResponse>("explain_response")),
+        params: config.modes.for_mode(LLMMode::Explain),
+    };
+
+    let response = client
+        .generate_with_parser(llm_request, parse_explain)
+        .await?;
+
+    match client.fallback_in_use() {
+        Some(fallback) => sp.stop(format!("[EXPLAIN MODE] Answered by fallback {}", fallback)),
+        None => sp.stop(""),
+    }
+
+    println!("\n{}", render_tree(command, &response));
+
+    Ok(())
+}
+
+/// Annotated tree: the command at the root, one branch per pipeline stage
+/// with its flags, arguments and redirections, then the side effects
+fn render_tree(command: &str, response: &ExplainResponse) -> String {
+    let mut out = format!("📖 {}\n", command.trim());
+    out.push_str(&format!("   {}\n\n", response.summary));
+
+    let count = response.stages.len();
+    for (i, stage) in response.stages.iter().enumerate() {
+        let last = i + 1 == count;
+        let (branch, indent) = if last {
+            ("└─ ", "   ")
+        } else {
+            ("├─ ", "│  ")
+        };
+        out.push_str(&format!("{}{}\n", branch, stage_label(stage)));
+        out.push_str(&format!("{}{}\n", indent, stage.description));
+
+        let width = stage
+            .parts
+            .iter()
+            .map(|p| p.text.chars().count())
+            .filter(|&w| w <= MAX_PART_WIDTH)
+            .max()
+            .unwrap_or(0);
+        let part_count = stage.parts.len();
+        for (j, part) in stage.parts.iter().enumerate() {
+            let part_branch = if j + 1 == part_count {
+                "└─ "
+            } else {
+                "├─ "
+            };
+            out.push_str(&format!(
+                "{}{}{:<width$}  {:<12} {}\n",
+                indent,
+                part_branch,
+                part.text,
+                part.kind.as_str(),
+                part.description,
+                width = width
+            ));
+        }
+
+        if !last {
+            out.push_str("│\n");
+        }
+    }
+
+    out.push('\n');
+    if response.side_effects.is_empty() {
+        out.push_str("✅ No side effects: the command only reads\n");
+    } else {
+        out.push_str("⚠️  Side effects\n");
+        for effect in &response.side_effects {
+            out.push_str(&format!("   • {}\n", effect));
+        }
+    }
+
+    out
+}
+
+/// Stage as written, prefixed by the operator joining it to the previous one
+fn stage_label(stage: &PipelineStage) -> String {
+    if stage.operator.is_empty() {
+        stage.command.clone()
+    } else {
+        format!("{} {}", stage.operator, stage.command)
+    }
+}
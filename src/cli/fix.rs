@@ -0,0 +1,217 @@
+use super::suggest::select_suggestion;
+use crate::llm::client::{LLMMode, LLMRequest};
+use crate::llm::{
+    LLMMessage, PromptBuilder, ResponseSchema, SuggestResponse, create_client, parse_suggest,
+};
+use crate::system::{
+    Config, ContextConfig, SystemInfo, get_spool_path, get_system_info, load_last_command,
+};
+use crate::tools::execute_command;
+use anyhow::Result;
+use cliclack::{confirm, spinner};
+use std::time::Duration;
+
+/// Longest a command may run when it is re-run to capture its error output
+const RERUN_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Failed command to diagnose
+struct FailedCommand {
+    command: String,
+    exit_code: Option<i32>,
+    stderr: Option<String>,
+}
+
+impl FailedCommand {
+    /// Describe the failure for the model, keeping the tail of long error output
+    fn to_message(&self, context: &ContextConfig) -> String {
+        let exit_code = self
+            .exit_code
+            .map(|code| code.to_string())
+            .unwrap_or_else(|| "unknown".to_string());
+        let stderr = match self.stderr {
+            Some(ref stderr) if !stderr.trim().is_empty() => tail(stderr, context),
+            _ => "(not captured)".to_string(),
+        };
+
+        format!(
+            "Command: {}\nExit code: {}\nError output:\n{}",
+            self.command, exit_code, stderr
+        )
+    }
+}
+
+/// Diagnose a failed command and suggest corrected ones.
+/// The command comes from the arguments, or from the shell hook's spool file.
+/// Returns the exit code of an executed fix (0 when nothing was run).
+pub async fn handle_fix_command(
+    config: &Config,
+    command: Option<String>,
+    exit_code: Option<i32>,
+    stderr: Option<String>,
+) -> Result<i32> {
+    let system_info = get_system_info();
+
+    let mut failed = match command {
+        Some(command) => FailedCommand {
+            command,
+            exit_code,
+            stderr,
+        },
+        None => {
+            let Some(last) = load_last_command()? else {
+                anyhow::bail!(
+                    "No failed command to fix. Pass it as `doum fix \"<command>\"`, or record the last command with the shell hook: eval \"$(doum fix --hook bash)\""
+                );
+            };
+            if last.exit_code == 0 && exit_code.is_none() {
+                println!("\n✅ The last command succeeded: {}\n", last.command);
+                return Ok(0);
+            }
+            FailedCommand {
+                command: last.command,
+                exit_code: exit_code.or(Some(last.exit_code)),
+                stderr,
+            }
+        }
+    };
+
+    // The hook cannot capture error output; offer to run the command again for it
+    if failed.stderr.is_none() && !rerun(&mut failed, &system_info)? {
+        return Ok(0);
+    }
+
+    let sp = spinner();
+    let client = create_client(&config.llm, &config.network)?
+        .budget(config.budget.clone())
+        .on_retry({
+            let sp = sp.clone();
+            move |notice| sp.set_message(format!("[FIX MODE] {}", notice))
+        });
+    let builder = PromptBuilder::new(system_info.clone());
+
+    sp.start(format!("[FIX MODE] Diagnosing `{}`...", failed.command));
+
+    let llm_request = LLMRequest {
+        mode: LLMMode::Fix,
+        system: builder.build_fix(),
+        messages: vec![LLMMessage::user(failed.to_message(&config.context))],
+        response_schema: Some(ResponseSchema::of::<SuggestResponse>("suggest_response")),
+        params: config.modes.for_mode(LLMMode::Fix),
+    };
+
+    let response = client
+        .generate_with_parser(llm_request, parse_suggest)
+        .await?;
+
+    match client.fallback_in_use() {
+        Some(fallback) => sp.stop(format!("[FIX MODE] Answered by fallback {}", fallback)),
+        None => sp.stop(""),
+    }
+
+    select_suggestion(&response, &system_info, false)
+}
+
+/// Re-run the failed command after confirmation to capture its exit code and error output.
+/// Returns false when the command succeeded this time, so there is nothing to fix.
+fn rerun(failed: &mut FailedCommand, system_info: &SystemInfo) -> Result<bool> {
+    let confirmed = confirm(format!(
+        "Re-run `{}` to capture its error output?",
+        failed.command
+    ))
+    .initial_value(false)
+    .interact()?;
+    if !confirmed {
+        return Ok(true);
+    }
+
+    let output = match execute_command(&failed.command, system_info, Some(RERUN_TIMEOUT)) {
+        Ok(output) => output,
+        Err(e) => {
+            println!("⚠️  Could not re-run the command: {}\n", e);
+            return Ok(true);
+        }
+    };
+    tracing::info!(
+        "Re-ran command `{}` with exit code {}",
+        failed.command,
+        output.exit_code
+    );
+
+    if output.success {
+        println!("\n✅ The command succeeded when re-run. Nothing to fix.\n");
+        return Ok(false);
+    }
+
+    // Some programs report errors on stdout
+    let stderr = if output.stderr.is_empty() {
+        output.stdout_string()
+    } else {
+        output.stderr_string()
+    };
+    failed.exit_code = Some(output.exit_code);
+    failed.stderr = Some(stderr);
+
+    Ok(true)
+}
+
+/// Last `context.max_lines` lines of the output, at most `context.max_size_kb`
+fn tail(text: &str, context: &ContextConfig) -> String {
+    let lines: Vec<&str> = text.trim_end().lines().collect();
+    let skipped = lines.len().saturating_sub(context.max_lines);
+    let mut tail = lines[skipped..].join("\n");
+
+    let max_bytes = context.max_size_kb * 1024;
+    if tail.len() > max_bytes {
+        let mut start = tail.len() - max_bytes;
+        while !tail.is_char_boundary(start) {
+            start += 1;
+        }
+        tail = tail[start..].to_string();
+    }
+
+    if skipped > 0 {
+        format!("... ({} lines omitted)\n{}", skipped, tail)
+    } else {
+        tail
+    }
+}
+
+/// Print the shell hook recording each command and its exit code for `doum fix`
+pub fn print_fix_hook(shell: &str) -> Result<()> {
+    let path = get_spool_path()?.display().to_string();
+
+    let hook = match shell {
+        "bash" => format!(
+            r#"__doum_spool() {{
+    local code=$?
+    printf '%s\n%s\n' "$code" "$(HISTTIMEFORMAT= history 1 | sed 's/^ *[0-9]* *//')" > '{}'
+    return $code
+}}
+PROMPT_COMMAND="__doum_spool${{PROMPT_COMMAND:+; $PROMPT_COMMAND}}""#,
+            path.replace('\'', r"'\''")
+        ),
+        "zsh" => format!(
+            r#"__doum_spool() {{
+    local code=$?
+    printf '%s\n%s\n' "$code" "$(fc -ln -1)" > '{}'
+}}
+autoload -Uz add-zsh-hook
+add-zsh-hook precmd __doum_spool"#,
+            path.replace('\'', r"'\''")
+        ),
+        "fish" => format!(
+            r#"function __doum_spool --on-event fish_postexec
+    set -l code $status
+    printf '%s\n%s\n' $code "$argv[1]" > '{}'
+end"#,
+            path.replace('\'', r"\'")
+        ),
+        _ => anyhow::bail!(
+            "Unsupported shell for the hook: {}. Use bash, zsh or fish",
+            shell
+        ),
+    };
+
+    println!("{}", hook);
+    Ok(())
+}
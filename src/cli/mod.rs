@@ -3,15 +3,25 @@
 pub mod args;
 pub mod ask;
 pub mod auto_mode;
+pub mod chat;
 pub mod config;
+pub mod explain;
+pub mod fix;
 pub mod secret;
+pub mod sessions;
 pub mod suggest;
 pub mod switch;
+pub mod usage;
 
-pub use args::{Cli, Commands, ConfigAction};
+pub use args::{Cli, Commands, ConfigAction, SessionsAction};
 pub use ask::handle_ask_command;
 pub use auto_mode::handle_auto_command;
+pub use chat::handle_chat_command;
 pub use config::handle_config_command;
+pub use explain::handle_explain_command;
+pub use fix::{handle_fix_command, print_fix_hook};
 pub use secret::handle_secret_command;
+pub use sessions::{handle_sessions_command, resume_session};
 pub use suggest::handle_suggest_command;
 pub use switch::handle_switch_command;
+pub use usage::handle_usage_command;
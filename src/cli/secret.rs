@@ -1,13 +1,27 @@
-use crate::llm::{AnthropicSecret, OpenAISecret, Provider, load_presets, verify_client};
-use crate::system::{LLMConfig, SecretManager};
+use crate::llm::{
+    AnthropicSecret, AzureOpenAISecret, CustomSecret, GeminiSecret, OpenAISecret, Provider,
+    load_presets, verify_client,
+};
+use crate::system::{LLMConfig, SecretManager, load_config};
 use anyhow::{Context, Result};
 use cliclack::{input, password, select, spinner};
 
 pub async fn handle_secret_command() -> Result<()> {
     cliclack::intro("🔐 Configure LLM Provider Secret")?;
 
-    // Step 1: Select provider
-    let providers = Provider::all();
+    // Step 1: Select provider (only those that need a secret)
+    let config = load_config()?;
+    let providers: Vec<_> = Provider::available(&config.llm)
+        .into_iter()
+        .filter(|p| match p {
+            Provider::Custom(name) => config
+                .llm
+                .providers
+                .get(name)
+                .is_some_and(|c| c.requires_secret()),
+            _ => p.requires_secret(),
+        })
+        .collect();
     let provider_items: Vec<_> = providers
         .iter()
         .map(|p| (p.as_str(), p.as_display(), ""))
@@ -21,7 +35,7 @@ pub async fn handle_secret_command() -> Result<()> {
     let provider: Provider = provider_str.parse()?;
 
     // Step 2: Input secrets based on provider
-    match provider {
+    match &provider {
         Provider::OpenAI => {
             let api_key = password("OpenAI API Key (required)")
                 .interact()
@@ -64,13 +78,79 @@ pub async fn handle_secret_command() -> Result<()> {
             };
             SecretManager::save(&provider, &secret)?;
         }
+        Provider::AzureOpenAI => {
+            let endpoint: String = input("Resource endpoint (required)")
+                .placeholder("https://my-resource.openai.azure.com")
+                .interact()
+                .context("Input failed")?;
+
+            let api_key = password("Azure OpenAI API Key (required)")
+                .interact()
+                .context("Password input failed")?;
+
+            let api_version: String = input("API version (press Enter for default)")
+                .placeholder(AzureOpenAISecret::DEFAULT_API_VERSION)
+                .required(false)
+                .interact()
+                .context("Input failed")?;
+
+            let secret = AzureOpenAISecret {
+                endpoint: endpoint.trim().to_string(),
+                api_key: api_key.trim().to_string(),
+                api_version: if api_version.trim().is_empty() {
+                    AzureOpenAISecret::DEFAULT_API_VERSION.to_string()
+                } else {
+                    api_version.trim().to_string()
+                },
+            };
+            SecretManager::save(&provider, &secret)?;
+        }
+        Provider::Gemini => {
+            let api_key = password("Gemini API Key (required)")
+                .interact()
+                .context("Password input failed")?;
+
+            let secret = GeminiSecret {
+                api_key: api_key.trim().to_string(),
+            };
+            SecretManager::save(&provider, &secret)?;
+        }
+        Provider::Ollama => {
+            anyhow::bail!("Ollama runs locally and does not need a secret");
+        }
+        Provider::Custom(name) => {
+            let api_key = password(format!("{} API Key (required)", name))
+                .interact()
+                .context("Password input failed")?;
+
+            let secret = CustomSecret {
+                api_key: api_key.trim().to_string(),
+            };
+            SecretManager::save(&provider, &secret)?;
+        }
     }
 
     // Step 3: Get first model and verification
-    let first_model = load_presets(&provider)
-        .first()
-        .map(|m| m.id.clone())
-        .context("No preset models available for this provider")?;
+    let first_model = match &provider {
+        Provider::Custom(name) => config
+            .llm
+            .providers
+            .get(name)
+            .and_then(|c| c.models.first().cloned()),
+        // Azure models are deployment names, so only the active one can be verified
+        Provider::AzureOpenAI => {
+            Some(config.llm.model.clone()).filter(|_| config.llm.provider == provider)
+        }
+        _ => load_presets(&provider).first().map(|m| m.id.clone()),
+    };
+
+    let Some(first_model) = first_model else {
+        cliclack::outro(format!(
+            "✅ {} secret saved (no model configured to verify with)",
+            provider_str
+        ))?;
+        return Ok(());
+    };
 
     let llm_config = LLMConfig {
         provider,
@@ -78,12 +158,13 @@ pub async fn handle_secret_command() -> Result<()> {
         timeout: 30,
         use_thinking: false,
         use_web_search: false,
+        ..config.llm
     };
 
     let sp = spinner();
     sp.start("Verifying API key...");
 
-    match verify_client(&llm_config).await {
+    match verify_client(&llm_config, &config.network).await {
         Ok(true) => {
             sp.stop(format!(
                 "{} secrets saved and verified successfully",
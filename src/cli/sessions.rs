@@ -0,0 +1,100 @@
+use crate::cli::SessionsAction;
+use crate::system::{Session, get_sessions_dir, list_sessions};
+use anyhow::{Context, Result};
+use std::fs;
+
+pub fn handle_sessions_command(action: SessionsAction) -> Result<()> {
+    match action {
+        SessionsAction::List => list(),
+        SessionsAction::Show { id } => {
+            println!("{}", Session::load(&id)?.to_markdown());
+            Ok(())
+        }
+        SessionsAction::Delete { id } => {
+            let session = Session::load(&id)?;
+            session.delete()?;
+            println!("🗑️  Deleted session {}", session.id);
+            Ok(())
+        }
+        SessionsAction::Export { id, format, output } => {
+            let session = Session::load(&id)?;
+            let content = match format.as_str() {
+                "md" | "markdown" => session.to_markdown(),
+                "json" => {
+                    serde_json::to_string_pretty(&session).context("Failed to serialize session")?
+                }
+                _ => anyhow::bail!("Unsupported export format: {}. Use md or json", format),
+            };
+
+            match output {
+                Some(path) => {
+                    fs::write(&path, content)
+                        .with_context(|| format!("Failed to write {}", path.display()))?;
+                    println!("💾 Exported session {} to {}", session.id, path.display());
+                }
+                None => print!("{}", content),
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Session to continue: the one given by id, the latest with `--continue`, or none
+pub fn resume_session(continue_latest: bool, id: Option<String>) -> Result<Option<Session>> {
+    if let Some(id) = id {
+        return Session::load(&id).map(Some);
+    }
+    if !continue_latest {
+        return Ok(None);
+    }
+
+    match Session::latest()? {
+        Some(session) => Ok(Some(session)),
+        None => anyhow::bail!("No session to continue. Ask without --continue to start one"),
+    }
+}
+
+/// Save a session after an exchange; a failure is reported without failing the command
+pub(crate) fn save_session(session: &mut Session) {
+    if let Err(e) = session.save() {
+        tracing::warn!("Failed to save session {}: {}", session.id, e);
+        eprintln!("⚠️  Failed to save session: {}", e);
+    }
+}
+
+fn list() -> Result<()> {
+    let sessions = list_sessions()?;
+
+    println!("💬 Sessions in {}\n", get_sessions_dir()?.display());
+    if sessions.is_empty() {
+        println!("No sessions saved.");
+        return Ok(());
+    }
+
+    let width = sessions
+        .iter()
+        .map(|s| s.id.len())
+        .max()
+        .unwrap_or_default();
+    println!(
+        "  {:<width$}  {:<16}  {:>8}  Title",
+        "ID", "Updated (UTC)", "Messages"
+    );
+    for session in &sessions {
+        let updated = session.updated_at;
+        println!(
+            "  {:<width$}  {} {:02}:{:02}  {:>8}  {}",
+            session.id,
+            updated.date(),
+            updated.hour(),
+            updated.minute(),
+            session.messages.len(),
+            session.title()
+        );
+    }
+    println!(
+        "\nContinue one with `doum ask --session <id> \"...\"`, or the latest with `doum ask --continue \"...\"`."
+    );
+
+    Ok(())
+}
@@ -1,32 +1,86 @@
-use crate::llm::client::LLMRequest;
-use crate::llm::{LLMMessage, PromptBuilder, create_client, parse_suggest};
-use crate::system::{get_system_info, load_config};
+use super::sessions::save_session;
+use crate::llm::client::{LLMMode, LLMRequest};
+use crate::llm::{
+    LLMMessage, PromptBuilder, ResponseSchema, SuggestResponse, create_client, parse_suggest,
+};
+use crate::system::{Config, Session, SystemInfo, get_system_info};
+use crate::tools::execute_command;
 use anyhow::Result;
 use arboard::Clipboard;
-use cliclack::{select, spinner};
+use cliclack::{confirm, select, spinner};
+use std::io::IsTerminal;
 
-pub async fn handle_suggest_command(request: &str) -> Result<()> {
-    let config = load_config()?;
-    let client = create_client(&config.llm)?;
+/// What to do with the selected command
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Action {
+    Copy,
+    Execute,
+}
+
+/// Suggest commands for a request and copy or run the selected one.
+/// Returns the exit code of the executed command (0 when nothing was run).
+/// With `exec`, the action menu is skipped and the command is executed after confirmation.
+pub async fn handle_suggest_command(config: &Config, request: &str, exec: bool) -> Result<i32> {
+    let sp = spinner();
+    let client = create_client(&config.llm, &config.network)?
+        .budget(config.budget.clone())
+        .on_retry({
+            let sp = sp.clone();
+            move |notice| sp.set_message(format!("[SUGGEST MODE] {}", notice))
+        });
     let system_info = get_system_info();
     let builder = PromptBuilder::new(system_info.clone());
 
-    let sp = spinner();
     sp.start("[SUGGEST MODE] Generating commands...");
 
     let llm_request = LLMRequest {
+        mode: LLMMode::Suggest,
         system: builder.build_suggest(),
         messages: vec![LLMMessage::user(request)],
+        response_schema: Some(ResponseSchema::of::<SuggestResponse>("suggest_response")),
+        params: config.modes.for_mode(LLMMode::Suggest),
     };
 
     let response = client.generate_with_parser(llm_request, parse_suggest).await?;
 
-    sp.stop("");
+    match client.fallback_in_use() {
+        Some(fallback) => sp.stop(format!("[SUGGEST MODE] Answered by fallback {}", fallback)),
+        None => sp.stop(""),
+    }
+
+    // Keep the exchange, so it can be followed up with `doum ask --continue`
+    let mut session = Session::new(config.llm.provider.clone(), config.llm.model.clone());
+    session.messages = vec![
+        LLMMessage::user(request),
+        LLMMessage::assistant(response.to_message()),
+    ];
+    save_session(&mut session);
+
+    select_suggestion(&response, &system_info, exec)
+}
 
+/// Let the user pick a suggested command, then copy or execute it.
+/// Without a terminal to pick from (scripts, pipes), the commands are printed instead.
+/// Returns the exit code of the executed command (0 when nothing was run).
+pub(crate) fn select_suggestion(
+    response: &SuggestResponse,
+    system_info: &SystemInfo,
+    exec: bool,
+) -> Result<i32> {
     // Handle empty suggestions
     if response.suggestions.is_empty() {
         println!("\n⚠️  No commands to suggest.\n");
-        return Ok(());
+        return Ok(0);
+    }
+
+    if !std::io::stdin().is_terminal() {
+        if exec {
+            anyhow::bail!("--exec needs an interactive terminal to confirm the command");
+        }
+        for suggestion in &response.suggestions {
+            println!("{}  # {}", suggestion.cmd, suggestion.description);
+        }
+        return Ok(0);
     }
 
     // Format command items for selection
@@ -38,12 +92,50 @@ pub async fn handle_suggest_command(request: &str) -> Result<()> {
 
     let selected = select("Select a command").items(&items).interact()?;
 
-    // Copy to clipboard
-    copy_to_clipboard(&selected.cmd)?;
+    let action = if exec {
+        Action::Execute
+    } else {
+        select("What do you want to do?")
+            .item(Action::Copy, "Copy to clipboard", "")
+            .item(Action::Execute, "Execute", system_info.shell.as_str())
+            .interact()?
+    };
 
-    cliclack::outro("✅ Command copied to clipboard, Ctrl+V to paste it!")?;
+    match action {
+        Action::Copy => {
+            copy_to_clipboard(&selected.cmd)?;
+            cliclack::outro("✅ Command copied to clipboard, Ctrl+V to paste it!")?;
+            Ok(0)
+        }
+        Action::Execute => run_command(&selected.cmd, system_info),
+    }
+}
 
-    Ok(())
+/// Run a command in the user's shell after confirmation, returning its exit code
+fn run_command(command: &str, system_info: &SystemInfo) -> Result<i32> {
+    let confirmed = confirm(format!("Run `{}`?", command))
+        .initial_value(false)
+        .interact()?;
+    if !confirmed {
+        cliclack::outro("Cancelled, nothing was run")?;
+        return Ok(0);
+    }
+
+    let output = execute_command(command, system_info, None)?;
+    tracing::info!(
+        "Executed command `{}` with exit code {}",
+        command,
+        output.exit_code
+    );
+
+    print!("{}", output.display());
+    if output.success {
+        cliclack::outro("✅ Command finished")?;
+    } else {
+        cliclack::outro(format!("❌ Command exited with code {}", output.exit_code))?;
+    }
+
+    Ok(output.exit_code)
 }
 
 /// Copy text to clipboard using arboard
@@ -1,13 +1,29 @@
-use crate::llm::{Provider, load_presets};
-use crate::system::{load_config, save_config};
+use crate::llm::discovery::{discover_models, merge_models, supports_discovery};
+use crate::llm::presets::ModelInfo;
+use crate::llm::{OllamaClient, OllamaConfig, Provider, load_presets};
+use crate::system::{Config, NetworkConfig, load_config, save_config};
 use anyhow::{Context, Result};
 use cliclack::{input, select};
 
-pub async fn handle_switch_command() -> Result<()> {
+pub async fn handle_switch_command(
+    provider: Option<String>,
+    model: Option<String>,
+    refresh: bool,
+    force: bool,
+) -> Result<()> {
+    match (provider, model) {
+        (Some(provider), Some(model)) => switch_to(&provider, &model, refresh, force).await,
+        _ => select_provider_and_model(refresh).await,
+    }
+}
+
+/// Interactive 2-step selection
+async fn select_provider_and_model(refresh: bool) -> Result<()> {
     cliclack::intro("🔄 Switch Provider & Model")?;
 
     // Step 1: Select provider
-    let providers = Provider::all();
+    let mut config = load_config()?;
+    let providers = Provider::available(&config.llm);
     let provider_items: Vec<_> = providers
         .iter()
         .map(|p| (p.as_str(), p.as_display(), ""))
@@ -21,7 +37,7 @@ pub async fn handle_switch_command() -> Result<()> {
     let provider: Provider = provider_str.parse()?;
 
     // Step 2: Select model for the chosen provider
-    let models = load_presets(&provider);
+    let models = load_models(&config, &provider, refresh).await;
     let mut model_items: Vec<_> = models
         .iter()
         .map(|m| (m.id.as_str(), m.name.as_str(), m.description.as_str()))
@@ -45,7 +61,6 @@ pub async fn handle_switch_command() -> Result<()> {
     };
 
     // Step 3: Update configuration
-    let mut config = load_config()?;
     config.llm.provider = provider;
     config.llm.model = model.clone();
     save_config(&config)?;
@@ -53,3 +68,114 @@ pub async fn handle_switch_command() -> Result<()> {
     cliclack::outro(format!("✅ Switched to {} - {}", provider_str, model))?;
     Ok(())
 }
+
+/// Switch without prompts, checking the model against the provider's model list
+async fn switch_to(provider_str: &str, model: &str, refresh: bool, force: bool) -> Result<()> {
+    let mut config = load_config()?;
+    let provider = Provider::parse_available(provider_str, &config.llm)?;
+
+    // Providers without a model list (e.g., Azure deployments) accept any name
+    if !force {
+        let models = load_models(&config, &provider, refresh).await;
+        if !models.is_empty() && !models.iter().any(|m| m.id == model) {
+            let ids: Vec<_> = models.iter().map(|m| m.id.as_str()).collect();
+            anyhow::bail!(
+                "Unknown model for {}: {}\nAvailable models: {}\nUse --force to switch anyway.",
+                provider.as_display(),
+                model,
+                ids.join(", ")
+            );
+        }
+    }
+
+    config.llm.provider = provider;
+    config.llm.model = model.to_string();
+    save_config(&config)?;
+
+    println!("✅ Switched to {} - {}", provider_str, model);
+    Ok(())
+}
+
+/// Models to offer for a provider
+async fn load_models(config: &Config, provider: &Provider, refresh: bool) -> Vec<ModelInfo> {
+    match provider {
+        Provider::Ollama => {
+            load_ollama_models(&config.llm.ollama_url, config.llm.timeout, &config.network).await
+        }
+        Provider::Custom(name) => config
+            .llm
+            .providers
+            .get(name)
+            .map(|c| {
+                c.models
+                    .iter()
+                    .map(|id| ModelInfo {
+                        id: id.clone(),
+                        name: id.clone(),
+                        description: String::new(),
+                        context_window: None,
+                        pricing: None,
+                    })
+                    .collect()
+            })
+            .unwrap_or_default(),
+        p if supports_discovery(p) => {
+            let listed = load_listed_models(config, provider, refresh).await;
+            merge_models(load_presets(provider), listed)
+        }
+        _ => load_presets(provider),
+    }
+}
+
+/// Ask the provider's API for its models (cached on disk)
+async fn load_listed_models(config: &Config, provider: &Provider, refresh: bool) -> Vec<ModelInfo> {
+    let sp = cliclack::spinner();
+    sp.start(format!("Fetching models from {}...", provider.as_display()));
+
+    match discover_models(&config.llm, &config.network, provider, refresh).await {
+        Ok(models) => {
+            sp.stop(format!("Found {} available models", models.len()));
+            models
+        }
+        Err(e) => {
+            tracing::warn!("Failed to list {} models: {}", provider.as_display(), e);
+            sp.error(format!(
+                "Could not list {} models, showing presets: {}",
+                provider.as_display(),
+                e
+            ));
+            vec![]
+        }
+    }
+}
+
+/// Query the running Ollama server for installed models
+async fn load_ollama_models(
+    base_url: &str,
+    timeout: u64,
+    network: &NetworkConfig,
+) -> Vec<ModelInfo> {
+    let sp = cliclack::spinner();
+    sp.start("Fetching models from Ollama server...");
+
+    let config = OllamaConfig {
+        model: String::new(),
+        base_url: base_url.to_string(),
+    };
+    let result = match OllamaClient::new(config, timeout, network) {
+        Ok(client) => client.list_models().await,
+        Err(e) => Err(e),
+    };
+
+    match result {
+        Ok(models) => {
+            sp.stop(format!("Found {} installed models", models.len()));
+            models
+        }
+        Err(e) => {
+            tracing::warn!("Failed to list Ollama models: {}", e);
+            sp.error(format!("Could not list Ollama models: {}", e));
+            vec![]
+        }
+    }
+}
@@ -0,0 +1,147 @@
+use crate::llm::budget::price_of;
+use crate::llm::{ModelPricing, Usage};
+use crate::system::{BudgetConfig, UsageRecord, load_usage};
+use anyhow::{Context, Result};
+use std::collections::BTreeMap;
+use time::OffsetDateTime;
+
+/// Token totals and estimated cost of a group of calls
+#[derive(Debug, Default)]
+struct UsageSummary {
+    requests: u64,
+    usage: Usage,
+    /// Estimated cost of the calls with known prices
+    cost: f64,
+    priced: bool,
+}
+
+impl UsageSummary {
+    fn add(&mut self, record: &UsageRecord, pricing: Option<ModelPricing>) {
+        self.requests += 1;
+        self.usage.input_tokens += record.usage.input_tokens;
+        self.usage.output_tokens += record.usage.output_tokens;
+        self.usage.cached_tokens += record.usage.cached_tokens;
+        self.usage.cache_write_tokens += record.usage.cache_write_tokens;
+        if let Some(pricing) = pricing {
+            self.cost += pricing.cost(&record.usage);
+            self.priced = true;
+        }
+    }
+
+    fn cost_display(&self) -> String {
+        if self.priced {
+            format!("${:.4}", self.cost)
+        } else {
+            "-".to_string()
+        }
+    }
+}
+
+pub fn handle_usage_command(budget: &BudgetConfig, month: Option<String>) -> Result<()> {
+    let month = match month {
+        Some(month) => parse_month(&month)?,
+        None => {
+            let now = OffsetDateTime::now_utc();
+            (now.year(), now.month() as u8)
+        }
+    };
+    let month_label = format!("{}-{:02}", month.0, month.1);
+
+    let records: Vec<_> = load_usage()?
+        .into_iter()
+        .filter(|r| (r.timestamp.year(), r.timestamp.month() as u8) == month)
+        .collect();
+
+    println!("📊 Token usage for {} (UTC)\n", month_label);
+
+    if records.is_empty() {
+        println!("No usage recorded.");
+        return Ok(());
+    }
+
+    let mut total = UsageSummary::default();
+    let mut by_day: BTreeMap<String, UsageSummary> = BTreeMap::new();
+    let mut by_model: BTreeMap<String, UsageSummary> = BTreeMap::new();
+    let mut by_mode: BTreeMap<String, UsageSummary> = BTreeMap::new();
+    let mut prices: BTreeMap<(String, String), Option<ModelPricing>> = BTreeMap::new();
+
+    for record in &records {
+        let pricing = *prices
+            .entry((record.provider.as_str().to_string(), record.model.clone()))
+            .or_insert_with(|| price_of(&record.provider, &record.model, budget));
+
+        total.add(record, pricing);
+        by_day
+            .entry(record.timestamp.date().to_string())
+            .or_default()
+            .add(record, pricing);
+        by_model
+            .entry(format!("{}/{}", record.provider.as_str(), record.model))
+            .or_default()
+            .add(record, pricing);
+        by_mode
+            .entry(record.mode.as_str().to_string())
+            .or_default()
+            .add(record, pricing);
+    }
+
+    println!(
+        "Total: {} requests, {} input ({} cached, {} written to cache), {} output tokens, estimated cost {}\n",
+        total.requests,
+        total.usage.input_tokens,
+        total.usage.cached_tokens,
+        total.usage.cache_write_tokens,
+        total.usage.output_tokens,
+        total.cost_display()
+    );
+
+    print_table("Day", &by_day);
+    print_table("Model", &by_model);
+    print_table("Mode", &by_mode);
+
+    if prices.values().any(Option::is_none) {
+        println!(
+            "Cost is estimated from preset prices; models without prices are shown as \"-\" (set budget.default_input_price and budget.default_output_price to price them)."
+        );
+    }
+
+    Ok(())
+}
+
+/// Parse a `YYYY-MM` month
+fn parse_month(month: &str) -> Result<(i32, u8)> {
+    let parsed = month.split_once('-').and_then(|(year, month)| {
+        let year = year.parse::<i32>().ok()?;
+        let month = month.parse::<u8>().ok()?;
+        (1..=12).contains(&month).then_some((year, month))
+    });
+
+    parsed.with_context(|| format!("Invalid month: {}. Expected YYYY-MM", month))
+}
+
+/// Print one breakdown table
+fn print_table(title: &str, rows: &BTreeMap<String, UsageSummary>) {
+    let width = rows
+        .keys()
+        .map(|key| key.len())
+        .chain([title.len()])
+        .max()
+        .unwrap_or_default();
+
+    println!(
+        "  {:<width$}  {:>8}  {:>12}  {:>12}  {:>12}  {:>10}",
+        title, "Requests", "Input", "Cached", "Output", "Cost"
+    );
+    for (key, summary) in rows {
+        println!(
+            "  {:<width$}  {:>8}  {:>12}  {:>12}  {:>12}  {:>10}",
+            key,
+            summary.requests,
+            summary.usage.input_tokens,
+            summary.usage.cached_tokens,
+            summary.usage.output_tokens,
+            summary.cost_display()
+        );
+    }
+    println!();
+}
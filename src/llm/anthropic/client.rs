@@ -1,103 +1,540 @@
 use crate::llm::anthropic::payloads::{
-    AnthropicConfig, AnthropicError, AnthropicRequest, AnthropicResponse,
+    AnthropicCacheControl, AnthropicCitation, AnthropicConfig, AnthropicDelta, AnthropicError,
+    AnthropicMessage, AnthropicModelList, AnthropicRequest, AnthropicResponse,
+    AnthropicStreamBlock, AnthropicStreamEvent, AnthropicSystemBlock, AnthropicThinking,
+    AnthropicTool, AnthropicToolChoice, AnthropicUsage, ContentBlock,
 };
-use crate::llm::client::{LLMClient, LLMRequest};
+use crate::llm::client::{
+    Citation, LLMClient, LLMMessage, LLMRequest, LLMResponse, StreamDelta, SystemPrompt, Usage,
+    estimated_text_tokens,
+};
+use crate::llm::error::LLMError;
+use crate::llm::http::client_builder;
+use crate::llm::presets::ModelInfo;
+use crate::llm::retry::retry_after;
+use crate::llm::stream::read_events;
+use crate::system::NetworkConfig;
 use anyhow::{Context, Result};
-use reqwest::Client;
+use reqwest::{Client, Response};
 use std::time::Duration;
 
 /// Anthropic LLM Client
 pub struct AnthropicClient {
     http_client: Client,
     config: AnthropicConfig,
+    timeout: Duration,
 }
 
 impl AnthropicClient {
+    /// Provider name used in errors
+    const PROVIDER: &'static str = "Anthropic";
+
     /// Anthropic API URL and Version
     const API_URL: &'static str = "https://api.anthropic.com/v1/messages";
+    const MODELS_URL: &'static str = "https://api.anthropic.com/v1/models";
     const API_VERSION: &'static str = "2023-06-01";
 
+    /// Output token limit when the mode does not set one.
+    /// Extended thinking gets its budget on top of it.
+    const MAX_TOKENS: u32 = 4096;
+
+    /// Highest temperature the Messages API accepts
+    const MAX_TEMPERATURE: f64 = 1.0;
+
+    /// Shortest prefix the API caches (Haiku models need twice as many tokens);
+    /// breakpoints on shorter prefixes are ignored, so none are set
+    const MIN_CACHE_TOKENS: u64 = 1024;
+
+    /// Server-side web search tool version and per-request search limit
+    const WEB_SEARCH_TOOL: &'static str = "web_search_20250305";
+    const WEB_SEARCH_MAX_USES: u32 = 5;
+
     /// Create a new AnthropicClient
-    pub fn new(config: AnthropicConfig, timeout: u64) -> Result<Self> {
+    pub fn new(config: AnthropicConfig, timeout: u64, network: &NetworkConfig) -> Result<Self> {
         if config.api_key.is_empty() {
-            anyhow::bail!(
-                "Anthropic API key is not set. Please configure it in the interactive config menu (doum config)."
-            );
+            return Err(LLMError::Auth {
+                provider: Self::PROVIDER.to_string(),
+                message: "API key is not set".to_string(),
+            }
+            .into());
         }
 
-        let http_client = Client::builder()
-            .timeout(Duration::from_secs(timeout))
+        let http_client = client_builder(network)?
             .build()
             .context("Failed to build HTTP client")?;
 
         Ok(Self {
             http_client,
             config,
+            timeout: Duration::from_secs(timeout),
         })
     }
-}
 
-#[async_trait::async_trait]
-impl LLMClient for AnthropicClient {
-    async fn generate(&self, request: LLMRequest) -> Result<String> {
-        let request_body = AnthropicRequest {
-            model: self.config.model.clone(),
-            system: Some(request.system),
-            messages: request.messages,
-            max_tokens: 4096,
+    /// List the models available to the API key (`GET /v1/models`), newest first
+    pub async fn list_models(&self) -> Result<Vec<ModelInfo>> {
+        let response = self
+            .http_client
+            .get(Self::MODELS_URL)
+            .query(&[("limit", "1000")])
+            .header("x-api-key", &self.config.api_key)
+            .header("anthropic-version", Self::API_VERSION)
+            .timeout(self.timeout)
+            .send()
+            .await
+            .map_err(|e| LLMError::from_reqwest(Self::PROVIDER, e))?;
+        let response = Self::check_status(response).await?;
+
+        let list: AnthropicModelList = response
+            .json()
+            .await
+            .map_err(|e| LLMError::from_reqwest(Self::PROVIDER, e))?;
+
+        let models = list
+            .data
+            .into_iter()
+            .map(|m| ModelInfo {
+                name: m.display_name.unwrap_or_else(|| m.id.clone()),
+                id: m.id,
+                description: String::new(),
+                context_window: None,
+                pricing: None,
+            })
+            .collect();
+
+        Ok(models)
+    }
+
+    /// Error for a response without any answer text
+    fn no_content() -> LLMError {
+        LLMError::BadResponse {
+            provider: Self::PROVIDER.to_string(),
+            message: "no content in response".to_string(),
+        }
+    }
+
+    /// Create a Messages API request payload
+    fn messages_request(&self, request: LLMRequest, stream: bool) -> AnthropicRequest {
+        // A structured answer is returned through a forced call to a tool taking the schema.
+        // Forced tool use does not allow extended thinking, and leaves no room for web search.
+        let structured = request.response_schema.is_some();
+        let params = request.params;
+        let max_tokens = params.max_tokens.unwrap_or(Self::MAX_TOKENS);
+        let effort = params
+            .effort(self.config.use_thinking)
+            .filter(|_| !structured);
+
+        // Thinking tokens count towards max_tokens, so extend the limit by the budget.
+        // Extended thinking only runs at the default temperature.
+        let (max_tokens, thinking, temperature) = match effort {
+            Some(effort) => (
+                max_tokens + effort.budget_tokens(),
+                Some(AnthropicThinking {
+                    thinking_type: "enabled".to_string(),
+                    budget_tokens: effort.budget_tokens(),
+                }),
+                None,
+            ),
+            None => (
+                max_tokens,
+                None,
+                params.temperature.map(|t| t.min(Self::MAX_TEMPERATURE)),
+            ),
         };
 
-        let response = self
+        let (tools, tool_choice) = match request.response_schema {
+            Some(schema) => (
+                Some(vec![AnthropicTool::Custom {
+                    name: schema.name.clone(),
+                    description: "Respond with the answer in this structure.".to_string(),
+                    input_schema: schema.schema,
+                }]),
+                Some(AnthropicToolChoice {
+                    choice_type: "tool".to_string(),
+                    name: schema.name,
+                }),
+            ),
+            None => (
+                self.config.use_web_search.then(|| {
+                    vec![AnthropicTool::WebSearch {
+                        tool_type: Self::WEB_SEARCH_TOOL.to_string(),
+                        name: "web_search".to_string(),
+                        max_uses: Self::WEB_SEARCH_MAX_USES,
+                    }]
+                }),
+                None,
+            ),
+        };
+
+        // The cached prefix runs through the tools, the system prompt and then the messages
+        let min_cache_tokens = self.min_cache_tokens();
+        let mut prefix_tokens = tools.as_ref().map_or(0, |tools| {
+            estimated_text_tokens(&serde_json::to_string(tools).unwrap_or_default())
+        }) + estimated_text_tokens(&request.system.text());
+        let system = Self::system_blocks(request.system, prefix_tokens >= min_cache_tokens);
+
+        // In chat and continued sessions, cache the history before the new message,
+        // so the next turn reads it back instead of paying for it again
+        let history_len = request.messages.len().saturating_sub(1);
+        prefix_tokens += request.messages[..history_len]
+            .iter()
+            .map(LLMMessage::estimated_tokens)
+            .sum::<u64>();
+        let mut messages: Vec<AnthropicMessage> =
+            request.messages.into_iter().map(Into::into).collect();
+        if history_len > 0 && prefix_tokens >= min_cache_tokens {
+            messages[history_len - 1].set_cache_breakpoint();
+        }
+
+        AnthropicRequest {
+            model: self.config.model.clone(),
+            system,
+            messages,
+            max_tokens,
+            temperature,
+            top_p: params.top_p,
+            thinking,
+            tools,
+            tool_choice,
+            stream,
+        }
+    }
+
+    /// Minimum prefix length for a cache breakpoint on the configured model
+    fn min_cache_tokens(&self) -> u64 {
+        if self.config.model.contains("haiku") {
+            Self::MIN_CACHE_TOKENS * 2
+        } else {
+            Self::MIN_CACHE_TOKENS
+        }
+    }
+
+    /// System prompt as text blocks. With `cache`, a breakpoint after the last cacheable
+    /// segment caches the tools and the system prompt up to it.
+    fn system_blocks(system: SystemPrompt, cache: bool) -> Vec<AnthropicSystemBlock> {
+        let segments: Vec<_> = system
+            .segments
+            .into_iter()
+            .filter(|s| !s.text.is_empty())
+            .collect();
+        let breakpoint = segments.iter().rposition(|s| s.cacheable).filter(|_| cache);
+
+        segments
+            .into_iter()
+            .enumerate()
+            .map(|(i, segment)| AnthropicSystemBlock {
+                block_type: "text".to_string(),
+                text: segment.text,
+                cache_control: (breakpoint == Some(i)).then(AnthropicCacheControl::ephemeral),
+            })
+            .collect()
+    }
+
+    /// Send a request and check the response status.
+    /// Streaming requests are only bounded until the response headers arrive.
+    async fn send(&self, request_body: &AnthropicRequest, timeout: Duration) -> Result<Response> {
+        let builder = self
             .http_client
             .post(Self::API_URL)
             .header("x-api-key", &self.config.api_key)
             .header("anthropic-version", Self::API_VERSION)
             .header("Content-Type", "application/json")
-            .json(&request_body)
-            .send()
-            .await
-            .map_err(|e| {
-                if e.is_timeout() {
-                    anyhow::anyhow!("Request timeout")
-                } else if e.is_connect() {
-                    anyhow::anyhow!("Failed to connect to Anthropic API")
-                } else {
-                    anyhow::anyhow!("Failed to send request to Anthropic API: {}", e)
-                }
-            })?;
+            .json(request_body);
+
+        let timeout_error = || LLMError::Timeout {
+            provider: Self::PROVIDER.to_string(),
+            message: format!("no response within {}s", timeout.as_secs()),
+        };
+        let result = if request_body.stream {
+            tokio::time::timeout(timeout, builder.send())
+                .await
+                .map_err(|_| timeout_error())?
+        } else {
+            builder.timeout(timeout).send().await
+        };
 
-        // Check response status
+        let response = result.map_err(|e| {
+            if e.is_timeout() {
+                timeout_error()
+            } else {
+                LLMError::from_reqwest(Self::PROVIDER, e)
+            }
+        })?;
+
+        Self::check_status(response).await
+    }
+
+    /// Turn an error status into an `LLMError`
+    async fn check_status(response: Response) -> Result<Response> {
         let status = response.status();
 
         if !status.is_success() {
+            let retry_after = retry_after(response.headers());
             let error_text = response
                 .text()
                 .await
                 .unwrap_or_else(|_| "Unknown error".to_string());
 
             // Try to parse Anthropic error format
-            if let Ok(anthropic_error) = serde_json::from_str::<AnthropicError>(&error_text) {
-                anyhow::bail!(
-                    "Anthropic API Error ({}): {}",
-                    status,
-                    anthropic_error.error.message
-                );
-            }
+            let (error_type, message) = match serde_json::from_str::<AnthropicError>(&error_text) {
+                Ok(anthropic_error) => (
+                    Some(anthropic_error.error.error_type),
+                    anthropic_error.error.message,
+                ),
+                Err(_) => (None, error_text),
+            };
 
-            anyhow::bail!("Anthropic API Error: {} - {}", status, error_text);
+            return Err(LLMError::from_status(
+                Self::PROVIDER,
+                status,
+                error_type.as_deref(),
+                message,
+                retry_after,
+            )
+            .into());
         }
 
+        Ok(response)
+    }
+}
+
+#[async_trait::async_trait]
+impl LLMClient for AnthropicClient {
+    async fn generate(&self, request: LLMRequest) -> Result<LLMResponse> {
+        let timeout = request.params.timeout_or(self.timeout);
+        let request_body = self.messages_request(request, false);
+
+        let response = self.send(&request_body, timeout).await?;
+
         // Parse response body
         let anthropic_response: AnthropicResponse = response
             .json()
             .await
-            .context("Failed to parse Anthropic response")?;
-
-        // Extract and return the generated content
-        anthropic_response
-            .content
-            .first()
-            .map(|block| block.text.clone())
-            .ok_or_else(|| anyhow::anyhow!("No content in Anthropic response"))
+            .map_err(|e| LLMError::from_reqwest(Self::PROVIDER, e))?;
+
+        // Join the text blocks (web search splits the answer around citations)
+        // and collect thinking blocks separately
+        let mut text = String::new();
+        let mut citations = Vec::new();
+        let mut thinking = Vec::new();
+        for block in anthropic_response.content {
+            match block {
+                ContentBlock::Text {
+                    text: block_text,
+                    citations: block_citations,
+                } => {
+                    text.push_str(&block_text);
+                    for citation in block_citations.unwrap_or_default() {
+                        if let AnthropicCitation::WebSearchResultLocation { url, title } = citation
+                        {
+                            Citation::push_unique(&mut citations, url, title);
+                        }
+                    }
+                }
+                ContentBlock::Thinking {
+                    thinking: block_thinking,
+                } => {
+                    thinking.push(block_thinking);
+                }
+                // Structured answer from the forced tool call
+                ContentBlock::ToolUse { input } => {
+                    text = input.to_string();
+                }
+                _ => {}
+            }
+        }
+
+        if text.is_empty() {
+            return Err(Self::no_content().into());
+        }
+
+        Ok(LLMResponse {
+            text,
+            reasoning: (!thinking.is_empty()).then(|| thinking.join("\n\n")),
+            citations,
+            usage: anthropic_response.usage.map(Usage::from),
+        })
+    }
+
+    async fn generate_stream(
+        &self,
+        request: LLMRequest,
+        on_delta: &mut (dyn for<'d> FnMut(StreamDelta<'d>) + Send),
+    ) -> Result<LLMResponse> {
+        let timeout = request.params.timeout_or(self.timeout);
+        let answer_tool = request.response_schema.as_ref().map(|s| s.name.clone());
+        let request_body = self.messages_request(request, true);
+
+        let response = self.send(&request_body, timeout).await?;
+
+        let mut answer = StreamedAnswer {
+            answer_tool,
+            ..Default::default()
+        };
+        read_events(response, Self::PROVIDER, timeout, |event| {
+            answer.handle(&event.data, on_delta)
+        })
+        .await?;
+
+        answer.finish()
+    }
+}
+
+/// Answer assembled from the events of a streamed response
+#[derive(Default)]
+struct StreamedAnswer {
+    /// Name of the forced tool whose input is the structured answer
+    answer_tool: Option<String>,
+    /// Content block of that tool call, once started. Other tool input (such as
+    /// web search queries) is streamed as well and is not part of the answer.
+    answer_block: Option<usize>,
+    text: String,
+    reasoning: String,
+    citations: Vec<Citation>,
+    usage: Option<AnthropicUsage>,
+}
+
+impl StreamedAnswer {
+    /// Handle one server-sent event, passing answer text and reasoning to `on_delta`
+    fn handle(
+        &mut self,
+        data: &str,
+        on_delta: &mut (dyn for<'d> FnMut(StreamDelta<'d>) + Send),
+    ) -> Result<()> {
+        let Ok(stream_event) = serde_json::from_str::<AnthropicStreamEvent>(data) else {
+            return Ok(());
+        };
+
+        match stream_event {
+            // Input tokens are reported at the start, output tokens with the final delta
+            AnthropicStreamEvent::MessageStart { message } => {
+                self.usage = message.usage;
+            }
+            AnthropicStreamEvent::MessageDelta {
+                usage: Some(delta_usage),
+            } => {
+                if let Some(ref mut usage) = self.usage {
+                    usage.output_tokens = delta_usage.output_tokens;
+                }
+            }
+            AnthropicStreamEvent::ContentBlockStart {
+                index,
+                content_block: AnthropicStreamBlock::ToolUse { name },
+            } if self.answer_tool.as_ref() == Some(&name) => {
+                self.answer_block = Some(index);
+            }
+            AnthropicStreamEvent::ContentBlockDelta {
+                delta: AnthropicDelta::TextDelta { text: delta },
+                ..
+            } => {
+                on_delta(StreamDelta::Text(&delta));
+                self.text.push_str(&delta);
+            }
+            AnthropicStreamEvent::ContentBlockDelta {
+                delta: AnthropicDelta::ThinkingDelta { thinking: delta },
+                ..
+            } => {
+                on_delta(StreamDelta::Reasoning(&delta));
+                self.reasoning.push_str(&delta);
+            }
+            AnthropicStreamEvent::ContentBlockDelta {
+                index,
+                delta:
+                    AnthropicDelta::InputJsonDelta {
+                        partial_json: delta,
+                    },
+            } if self.answer_block == Some(index) => {
+                on_delta(StreamDelta::Text(&delta));
+                self.text.push_str(&delta);
+            }
+            AnthropicStreamEvent::ContentBlockDelta {
+                delta:
+                    AnthropicDelta::CitationsDelta {
+                        citation: AnthropicCitation::WebSearchResultLocation { url, title },
+                    },
+                ..
+            } => {
+                Citation::push_unique(&mut self.citations, url, title);
+            }
+            AnthropicStreamEvent::Error { error } => {
+                return Err(LLMError::from_event(
+                    AnthropicClient::PROVIDER,
+                    Some(&error.error_type),
+                    error.message,
+                )
+                .into());
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Final response once the stream has ended
+    fn finish(self) -> Result<LLMResponse> {
+        if self.text.is_empty() {
+            return Err(AnthropicClient::no_content().into());
+        }
+
+        Ok(LLMResponse {
+            text: self.text,
+            reasoning: (!self.reasoning.is_empty()).then_some(self.reasoning),
+            citations: self.citations,
+            usage: self.usage.map(Usage::from),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stream(answer_tool: Option<&str>, events: &[&str]) -> (LLMResponse, String) {
+        let mut answer = StreamedAnswer {
+            answer_tool: answer_tool.map(str::to_string),
+            ..Default::default()
+        };
+        let mut printed = String::new();
+        let mut on_delta = |delta: StreamDelta<'_>| {
+            if let StreamDelta::Text(text) = delta {
+                printed.push_str(text);
+            }
+        };
+        for event in events {
+            answer.handle(event, &mut on_delta).unwrap();
+        }
+        (answer.finish().unwrap(), printed)
+    }
+
+    #[test]
+    fn web_search_query_stays_out_of_the_answer() {
+        let (response, printed) = stream(
+            None,
+            &[
+                r#"{"type":"content_block_start","index":0,"content_block":{"type":"server_tool_use","id":"srvtoolu_1","name":"web_search","input":{}}}"#,
+                r#"{"type":"content_block_delta","index":0,"delta":{"type":"input_json_delta","partial_json":"{\"query\": \"rust "}}"#,
+                r#"{"type":"content_block_delta","index":0,"delta":{"type":"input_json_delta","partial_json":"release\"}"}}"#,
+                r#"{"type":"content_block_stop","index":0}"#,
+                r#"{"type":"content_block_start","index":1,"content_block":{"type":"web_search_tool_result","tool_use_id":"srvtoolu_1","content":[]}}"#,
+                r#"{"type":"content_block_start","index":2,"content_block":{"type":"text","text":""}}"#,
+                r#"{"type":"content_block_delta","index":2,"delta":{"type":"text_delta","text":"Rust 1.90 is out."}}"#,
+            ],
+        );
+
+        assert_eq!(response.text, "Rust 1.90 is out.");
+        assert_eq!(printed, "Rust 1.90 is out.");
+    }
+
+    #[test]
+    fn structured_answer_comes_from_the_forced_tool_only() {
+        let (response, _) = stream(
+            Some("suggest_response"),
+            &[
+                r#"{"type":"content_block_start","index":0,"content_block":{"type":"tool_use","id":"toolu_1","name":"suggest_response","input":{}}}"#,
+                r#"{"type":"content_block_delta","index":0,"delta":{"type":"input_json_delta","partial_json":"{\"suggestions\": "}}"#,
+                r#"{"type":"content_block_delta","index":0,"delta":{"type":"input_json_delta","partial_json":"[]}"}}"#,
+                r#"{"type":"content_block_delta","index":1,"delta":{"type":"input_json_delta","partial_json":"{\"other\": 1}"}}"#,
+            ],
+        );
+
+        assert_eq!(response.text, r#"{"suggestions": []}"#);
     }
 }
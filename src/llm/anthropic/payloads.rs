@@ -1,32 +1,321 @@
-use crate::llm::LLMMessage;
+use crate::llm::client::{ContentPart, LLMMessage, Usage};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AnthropicConfig {
     pub model: String,
     pub api_key: String,
+    pub use_thinking: bool,
+    pub use_web_search: bool,
 }
 
 #[derive(Debug, Serialize)]
 pub(crate) struct AnthropicRequest {
     pub model: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub system: Option<String>,
-    pub messages: Vec<LLMMessage>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub system: Vec<AnthropicSystemBlock>,
+    pub messages: Vec<AnthropicMessage>,
     pub max_tokens: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thinking: Option<AnthropicThinking>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tools: Option<Vec<AnthropicTool>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_choice: Option<AnthropicToolChoice>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub stream: bool,
+}
+
+/// Request message; text-only content is sent as a plain string
+#[derive(Debug, Serialize)]
+pub(crate) struct AnthropicMessage {
+    pub role: String,
+    pub content: AnthropicContent,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub(crate) enum AnthropicContent {
+    Text(String),
+    Blocks(Vec<AnthropicInputBlock>),
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub(crate) enum AnthropicInputBlock {
+    Text {
+        text: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        cache_control: Option<AnthropicCacheControl>,
+    },
+    Image {
+        source: AnthropicImageSource,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        cache_control: Option<AnthropicCacheControl>,
+    },
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct AnthropicImageSource {
+    #[serde(rename = "type")]
+    pub source_type: String,
+    pub media_type: String,
+    pub data: String,
+}
+
+impl From<LLMMessage> for AnthropicMessage {
+    fn from(message: LLMMessage) -> Self {
+        let role = message.role.as_str().to_string();
+        let content = if message.images().next().is_none() {
+            AnthropicContent::Text(message.text())
+        } else {
+            AnthropicContent::Blocks(
+                message
+                    .content
+                    .into_iter()
+                    .map(|part| match part {
+                        ContentPart::Text { text } => AnthropicInputBlock::Text {
+                            text,
+                            cache_control: None,
+                        },
+                        ContentPart::Image(image) => AnthropicInputBlock::Image {
+                            source: AnthropicImageSource {
+                                source_type: "base64".to_string(),
+                                media_type: image.media_type,
+                                data: image.data,
+                            },
+                            cache_control: None,
+                        },
+                    })
+                    .collect(),
+            )
+        };
+        Self { role, content }
+    }
+}
+
+impl AnthropicMessage {
+    /// Cache the conversation up to and including this message
+    pub fn set_cache_breakpoint(&mut self) {
+        if let AnthropicContent::Text(text) = &mut self.content {
+            self.content = AnthropicContent::Blocks(vec![AnthropicInputBlock::Text {
+                text: std::mem::take(text),
+                cache_control: None,
+            }]);
+        }
+        if let AnthropicContent::Blocks(blocks) = &mut self.content
+            && let Some(
+                AnthropicInputBlock::Text { cache_control, .. }
+                | AnthropicInputBlock::Image { cache_control, .. },
+            ) = blocks.last_mut()
+        {
+            *cache_control = Some(AnthropicCacheControl::ephemeral());
+        }
+    }
+}
+
+/// System prompt text block; `cache_control` caches the prompt up to and including it
+#[derive(Debug, Serialize)]
+pub(crate) struct AnthropicSystemBlock {
+    #[serde(rename = "type")]
+    pub block_type: String,
+    pub text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cache_control: Option<AnthropicCacheControl>,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct AnthropicCacheControl {
+    #[serde(rename = "type")]
+    pub control_type: String,
+}
+
+impl AnthropicCacheControl {
+    /// Default cache lifetime (five minutes, refreshed on each hit)
+    pub fn ephemeral() -> Self {
+        Self {
+            control_type: "ephemeral".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct AnthropicThinking {
+    #[serde(rename = "type")]
+    pub thinking_type: String,
+    pub budget_tokens: u32,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub(crate) enum AnthropicTool {
+    /// Server-side web search tool
+    WebSearch {
+        #[serde(rename = "type")]
+        tool_type: String,
+        name: String,
+        max_uses: u32,
+    },
+    /// Client tool whose input is the structured answer
+    Custom {
+        name: String,
+        description: String,
+        input_schema: serde_json::Value,
+    },
+}
+
+/// Force the model to call the named tool
+#[derive(Debug, Serialize)]
+pub(crate) struct AnthropicToolChoice {
+    #[serde(rename = "type")]
+    pub choice_type: String,
+    pub name: String,
 }
 
 #[derive(Debug, Deserialize)]
 pub(crate) struct AnthropicResponse {
     pub content: Vec<ContentBlock>,
+    #[serde(default)]
+    pub usage: Option<AnthropicUsage>,
+}
+
+/// Token usage; `input_tokens` excludes the tokens read from or written to the cache
+#[derive(Debug, Default, Deserialize)]
+pub(crate) struct AnthropicUsage {
+    #[serde(default)]
+    pub input_tokens: u64,
+    #[serde(default)]
+    pub output_tokens: u64,
+    #[serde(default)]
+    pub cache_creation_input_tokens: Option<u64>,
+    #[serde(default)]
+    pub cache_read_input_tokens: Option<u64>,
+}
+
+impl From<AnthropicUsage> for Usage {
+    fn from(usage: AnthropicUsage) -> Self {
+        let cache_read = usage.cache_read_input_tokens.unwrap_or_default();
+        let cache_write = usage.cache_creation_input_tokens.unwrap_or_default();
+        Usage {
+            input_tokens: usage.input_tokens + cache_read + cache_write,
+            output_tokens: usage.output_tokens,
+            cached_tokens: cache_read,
+            cache_write_tokens: cache_write,
+        }
+    }
 }
 
 #[derive(Debug, Deserialize)]
-pub(crate) struct ContentBlock {
-    #[serde(rename = "type")]
-    #[allow(dead_code)]
-    pub block_type: String,
-    pub text: String,
+#[serde(tag = "type")]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum ContentBlock {
+    Text {
+        text: String,
+        #[serde(default)]
+        citations: Option<Vec<AnthropicCitation>>,
+    },
+    Thinking {
+        thinking: String,
+    },
+    ToolUse {
+        input: serde_json::Value,
+    },
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type")]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum AnthropicCitation {
+    WebSearchResultLocation {
+        url: String,
+        title: Option<String>,
+    },
+    #[serde(other)]
+    Other,
+}
+
+/// Server-sent event of the Messages API
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type")]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum AnthropicStreamEvent {
+    MessageStart {
+        message: AnthropicStreamMessage,
+    },
+    MessageDelta {
+        #[serde(default)]
+        usage: Option<AnthropicUsage>,
+    },
+    ContentBlockStart {
+        index: usize,
+        content_block: AnthropicStreamBlock,
+    },
+    ContentBlockDelta {
+        index: usize,
+        delta: AnthropicDelta,
+    },
+    Error {
+        error: ErrorDetail,
+    },
+    #[serde(other)]
+    Other,
+}
+
+/// Content block opened by `content_block_start`; only client tool calls matter
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type")]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum AnthropicStreamBlock {
+    ToolUse {
+        name: String,
+    },
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct AnthropicStreamMessage {
+    #[serde(default)]
+    pub usage: Option<AnthropicUsage>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type")]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum AnthropicDelta {
+    TextDelta {
+        text: String,
+    },
+    ThinkingDelta {
+        thinking: String,
+    },
+    CitationsDelta {
+        citation: AnthropicCitation,
+    },
+    InputJsonDelta {
+        partial_json: String,
+    },
+    #[serde(other)]
+    Other,
+}
+
+/// `GET /v1/models` response
+#[derive(Debug, Deserialize)]
+pub(crate) struct AnthropicModelList {
+    pub data: Vec<AnthropicModel>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct AnthropicModel {
+    pub id: String,
+    #[serde(default)]
+    pub display_name: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -35,7 +324,6 @@ pub(crate) struct AnthropicError {
 }
 
 #[derive(Debug, Deserialize)]
-#[allow(dead_code)]
 pub(crate) struct ErrorDetail {
     pub message: String,
     #[serde(rename = "type")]
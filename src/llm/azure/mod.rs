@@ -0,0 +1,8 @@
+//! Azure OpenAI integration module.
+//!
+//! Requests are served by [`OpenAIClient`](crate::llm::OpenAIClient) against the
+//! deployment URL of the configured Azure resource.
+
+pub mod secret;
+
+pub use secret::AzureOpenAISecret;
@@ -0,0 +1,47 @@
+use crate::system::ProviderSecret;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+/// Azure OpenAI Secret information
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AzureOpenAISecret {
+    /// Resource endpoint (e.g., https://my-resource.openai.azure.com)
+    pub endpoint: String,
+
+    pub api_key: String,
+
+    pub api_version: String,
+}
+
+impl AzureOpenAISecret {
+    /// Default `api-version` query parameter
+    pub const DEFAULT_API_VERSION: &'static str = "2024-10-21";
+}
+
+impl ProviderSecret for AzureOpenAISecret {
+    fn validate(&self) -> Result<()> {
+        if !self.endpoint.starts_with("https://") {
+            anyhow::bail!("Endpoint must be an https:// URL");
+        }
+        if self.api_key.is_empty() {
+            anyhow::bail!("API key cannot be empty");
+        }
+        if self.api_version.is_empty() {
+            anyhow::bail!("API version cannot be empty");
+        }
+        Ok(())
+    }
+
+    fn masked(&self) -> String {
+        let key = if self.api_key.len() > 10 {
+            format!(
+                "{}...{}",
+                &self.api_key[..4],
+                &self.api_key[self.api_key.len() - 4..]
+            )
+        } else {
+            "***".to_string()
+        };
+        format!("{} ({})", key, self.endpoint)
+    }
+}
@@ -0,0 +1,97 @@
+use crate::llm::error::LLMError;
+use crate::llm::{ModelPricing, Provider, find_pricing};
+use crate::system::{BudgetConfig, load_usage};
+use anyhow::Result;
+use std::collections::HashMap;
+use time::OffsetDateTime;
+
+/// Estimated spend in USD, from the usage ledger and the preset prices
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Spend {
+    /// Spent today (UTC)
+    pub daily_usd: f64,
+    /// Spent this calendar month (UTC)
+    pub monthly_usd: f64,
+}
+
+/// Price of a model: its preset, free for local Ollama models,
+/// otherwise `budget.default_input_price`/`default_output_price` when set
+pub fn price_of(provider: &Provider, model: &str, budget: &BudgetConfig) -> Option<ModelPricing> {
+    if *provider == Provider::Ollama {
+        return Some(ModelPricing {
+            input: 0.0,
+            cached_input: None,
+            cache_write: None,
+            output: 0.0,
+        });
+    }
+    find_pricing(provider, model).or_else(|| budget.default_price())
+}
+
+/// Sum the estimated cost of the calls recorded today and this month.
+/// Calls to models without a price are not counted.
+pub fn current_spend(budget: &BudgetConfig) -> Result<Spend> {
+    let now = OffsetDateTime::now_utc();
+    let mut prices = HashMap::new();
+    let mut spend = Spend::default();
+
+    for record in load_usage()? {
+        let timestamp = record.timestamp;
+        if (timestamp.year(), timestamp.month()) != (now.year(), now.month()) {
+            continue;
+        }
+
+        let pricing = *prices
+            .entry((record.provider.clone(), record.model.clone()))
+            .or_insert_with(|| price_of(&record.provider, &record.model, budget));
+        let Some(pricing) = pricing else {
+            continue;
+        };
+
+        let cost = pricing.cost(&record.usage);
+        spend.monthly_usd += cost;
+        if timestamp.date() == now.date() {
+            spend.daily_usd += cost;
+        }
+    }
+
+    Ok(spend)
+}
+
+/// Refuse to send another request once a daily or monthly limit is reached,
+/// or when a limit is set but the model's spend cannot be estimated
+pub(crate) fn check_budget(provider: &Provider, model: &str, budget: &BudgetConfig) -> Result<()> {
+    if !budget.has_limit() {
+        return Ok(());
+    }
+
+    if price_of(provider, model, budget).is_none() {
+        return Err(LLMError::BudgetExceeded {
+            provider: provider.as_display(),
+            message: format!(
+                "{} has no known price, so its spend cannot be checked against the limit",
+                model
+            ),
+        }
+        .into());
+    }
+
+    let spend = current_spend(budget)?;
+    let limits = [
+        ("daily", spend.daily_usd, budget.daily_usd),
+        ("monthly", spend.monthly_usd, budget.monthly_usd),
+    ];
+    for (period, spent, limit) in limits {
+        if let Some(limit) = limit
+            && spent >= limit
+        {
+            return Err(LLMError::BudgetExceeded {
+                provider: provider.as_display(),
+                message: format!("spent ${:.4} of the ${:.2} {} budget", spent, limit, period),
+            }
+            .into());
+        }
+    }
+
+    Ok(())
+}
@@ -1,10 +1,24 @@
+use crate::llm::budget::check_budget;
+use crate::llm::error::LLMError;
+use crate::llm::presets::ModelInfo;
+use crate::llm::replay::{ReplayClient, ReplayMode};
+use crate::llm::retry::{RetryNotice, plan_retry};
 use crate::llm::{
-    AnthropicClient, AnthropicConfig, AnthropicSecret, OpenAIClient, OpenAIConfig, Provider,
+    AnthropicClient, AnthropicConfig, AnthropicSecret, AzureOpenAISecret, CustomSecret,
+    GeminiClient, GeminiConfig, GeminiSecret, OllamaClient, OllamaConfig, OpenAIApi, OpenAIAuth,
+    OpenAIClient, OpenAIConfig, Provider,
+};
+use crate::system::{
+    BudgetConfig, NetworkConfig, ProviderSecret, SecretManager, UsageRecord, append_usage,
 };
-use crate::system::SecretManager;
 use crate::{llm::OpenAISecret, system::LLMConfig};
 use anyhow::{Context, Result};
+use schemars::JsonSchema;
+use schemars::generate::SchemaSettings;
 use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
 
 /// LLM Message Role
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -14,18 +28,320 @@ pub enum LLMRole {
     Assistant,
 }
 
+impl LLMRole {
+    /// Convert to string representation
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LLMRole::User => "user",
+            LLMRole::Assistant => "assistant",
+        }
+    }
+}
+
+/// Feature that issued a request, recorded with its token usage
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LLMMode {
+    #[default]
+    Ask,
+    Suggest,
+    Auto,
+    Explain,
+    Fix,
+    Chat,
+    /// API key verification by `doum secret`
+    Verify,
+}
+
+impl LLMMode {
+    /// Convert to string representation
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LLMMode::Ask => "ask",
+            LLMMode::Suggest => "suggest",
+            LLMMode::Auto => "auto",
+            LLMMode::Explain => "explain",
+            LLMMode::Fix => "fix",
+            LLMMode::Chat => "chat",
+            LLMMode::Verify => "verify",
+        }
+    }
+}
+
 /// LLM Request
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LLMRequest {
-    pub system: String,
+    #[serde(default)]
+    pub mode: LLMMode,
+    pub system: SystemPrompt,
     pub messages: Vec<LLMMessage>,
+    /// Schema the answer must follow, for providers with structured outputs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response_schema: Option<ResponseSchema>,
+    /// Sampling, length and timeout settings of the mode (`[modes.<mode>]`)
+    #[serde(default, skip_serializing_if = "GenerationParams::is_default")]
+    pub params: GenerationParams,
+}
+
+impl LLMRequest {
+    /// Rough token count of an image (providers scale large images to about a megapixel)
+    const IMAGE_TOKENS: u64 = 1600;
+
+    /// Rough input token count (about 4 characters per token)
+    pub fn estimated_input_tokens(&self) -> u64 {
+        estimated_text_tokens(&self.system.text())
+            + self
+                .messages
+                .iter()
+                .map(LLMMessage::estimated_tokens)
+                .sum::<u64>()
+    }
+}
+
+/// How much a reasoning model thinks before answering
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReasoningEffort {
+    Minimal,
+    Low,
+    Medium,
+    High,
+}
+
+impl ReasoningEffort {
+    /// Convert to string representation (OpenAI `reasoning.effort`)
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReasoningEffort::Minimal => "minimal",
+            ReasoningEffort::Low => "low",
+            ReasoningEffort::Medium => "medium",
+            ReasoningEffort::High => "high",
+        }
+    }
+
+    /// Thinking token budget, for providers that take one (Anthropic, Gemini)
+    pub fn budget_tokens(&self) -> u32 {
+        match self {
+            ReasoningEffort::Minimal => 1024,
+            ReasoningEffort::Low => 2048,
+            ReasoningEffort::Medium => 4096,
+            ReasoningEffort::High => 16384,
+        }
+    }
+}
+
+impl FromStr for ReasoningEffort {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "minimal" => Ok(ReasoningEffort::Minimal),
+            "low" => Ok(ReasoningEffort::Low),
+            "medium" => Ok(ReasoningEffort::Medium),
+            "high" => Ok(ReasoningEffort::High),
+            _ => anyhow::bail!(
+                "Invalid reasoning effort: {}. Use minimal, low, medium or high",
+                s
+            ),
+        }
+    }
+}
+
+/// Generation settings of a mode. Unset fields keep the provider's defaults.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct GenerationParams {
+    /// Output token limit
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f64>,
+    /// Request timeout in seconds, replacing `llm.timeout`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<u64>,
+    /// Enables reasoning for the mode, even when `llm.use_thinking` is off
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reasoning_effort: Option<ReasoningEffort>,
+}
+
+impl GenerationParams {
+    /// Whether nothing is set (left out of `config.toml`)
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Timeout of the mode, or `default` (`llm.timeout`) when unset
+    pub fn timeout_or(&self, default: Duration) -> Duration {
+        self.timeout.map_or(default, Duration::from_secs)
+    }
+
+    /// Reasoning effort in effect: the mode's, or medium when `llm.use_thinking` is on
+    pub fn effort(&self, use_thinking: bool) -> Option<ReasoningEffort> {
+        self.reasoning_effort
+            .or(use_thinking.then_some(ReasoningEffort::Medium))
+    }
+}
+
+/// Named JSON schema for structured outputs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResponseSchema {
+    /// Schema name (`[A-Za-z0-9_-]`), also used as the Anthropic tool name
+    pub name: String,
+    pub schema: serde_json::Value,
+}
+
+impl ResponseSchema {
+    /// Generate the schema of `T`, with nested types inlined
+    pub fn of<T: JsonSchema>(name: &str) -> Self {
+        let generator = SchemaSettings::draft2020_12()
+            .with(|s| s.inline_subschemas = true)
+            .into_generator();
+        let mut schema = generator.into_root_schema_for::<T>().to_value();
+
+        // Keep only the schema itself; some providers reject the meta keywords
+        if let Some(object) = schema.as_object_mut() {
+            object.remove("$schema");
+            object.remove("title");
+        }
+
+        Self {
+            name: name.to_string(),
+            schema,
+        }
+    }
+}
+
+/// LLM Response
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LLMResponse {
+    pub text: String,
+    /// Reasoning summary or thinking text, when the provider returned one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reasoning: Option<String>,
+    /// Web sources cited by the answer, in order of first appearance
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub citations: Vec<Citation>,
+    /// Token counts, when the provider reported them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usage: Option<Usage>,
+}
+
+/// Token usage of a single call
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Usage {
+    /// All input tokens, including the cached ones
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    /// Input tokens read from the provider's prompt cache
+    pub cached_tokens: u64,
+    /// Input tokens written to the provider's prompt cache (Anthropic)
+    #[serde(default)]
+    pub cache_write_tokens: u64,
+}
+
+/// Web source cited by a response
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Citation {
+    pub url: String,
+    pub title: Option<String>,
+}
+
+impl Citation {
+    /// Append a citation unless its URL is already listed
+    pub(crate) fn push_unique(citations: &mut Vec<Citation>, url: String, title: Option<String>) {
+        if !citations.iter().any(|c| c.url == url) {
+            citations.push(Citation { url, title });
+        }
+    }
+}
+
+/// Incremental piece of a streamed response
+#[derive(Debug, Clone, Copy)]
+pub enum StreamDelta<'a> {
+    Text(&'a str),
+    Reasoning(&'a str),
+}
+
+/// Section of a system prompt
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptSegment {
+    pub text: String,
+    /// The prompt up to and including this segment is identical across calls,
+    /// so providers with prompt caching may cache it
+    #[serde(default)]
+    pub cacheable: bool,
+}
+
+/// System prompt made of segments. Providers without prompt caching receive them joined.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SystemPrompt {
+    pub segments: Vec<PromptSegment>,
+}
+
+impl SystemPrompt {
+    /// Separator between segments sent as a single string
+    pub const SEPARATOR: &'static str = "\n\n---\n\n";
+
+    /// Append a segment that is identical across calls
+    pub fn cacheable(mut self, text: impl Into<String>) -> Self {
+        self.segments.push(PromptSegment {
+            text: text.into(),
+            cacheable: true,
+        });
+        self
+    }
+
+    /// Whole prompt as a single string
+    pub fn text(&self) -> String {
+        self.segments
+            .iter()
+            .map(|s| s.text.as_str())
+            .collect::<Vec<_>>()
+            .join(Self::SEPARATOR)
+    }
+}
+
+impl From<&str> for SystemPrompt {
+    fn from(text: &str) -> Self {
+        Self {
+            segments: vec![PromptSegment {
+                text: text.to_string(),
+                cacheable: false,
+            }],
+        }
+    }
+}
+
+/// Image attached to a message
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImageData {
+    /// MIME type (`image/png`, `image/jpeg`, `image/gif` or `image/webp`)
+    pub media_type: String,
+    /// Base64-encoded image bytes
+    pub data: String,
+}
+
+impl ImageData {
+    /// `data:` URL embedding the image, as taken by the OpenAI APIs
+    pub fn data_url(&self) -> String {
+        format!("data:{};base64,{}", self.media_type, self.data)
+    }
+}
+
+/// Part of a message's content
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum ContentPart {
+    Text { text: String },
+    Image(ImageData),
 }
 
 /// LLM Message
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LLMMessage {
     pub role: LLMRole,
-    pub content: String,
+    pub content: Vec<ContentPart>,
 }
 
 impl LLMMessage {
@@ -33,81 +349,452 @@ impl LLMMessage {
     pub fn user(content: impl Into<String>) -> Self {
         Self {
             role: LLMRole::User,
-            content: content.into(),
+            content: vec![ContentPart::Text {
+                text: content.into(),
+            }],
         }
     }
 
+    /// create user message with attached images, placed before the text
+    pub fn user_with_images(content: impl Into<String>, images: Vec<ImageData>) -> Self {
+        let mut message = Self::user(content);
+        message
+            .content
+            .splice(0..0, images.into_iter().map(ContentPart::Image));
+        message
+    }
+
     /// create assistant message
     pub fn assistant(content: impl Into<String>) -> Self {
         Self {
             role: LLMRole::Assistant,
-            content: content.into(),
+            content: vec![ContentPart::Text {
+                text: content.into(),
+            }],
+        }
+    }
+
+    /// Text of the message, without the images
+    pub fn text(&self) -> String {
+        self.content
+            .iter()
+            .filter_map(|part| match part {
+                ContentPart::Text { text } => Some(text.as_str()),
+                ContentPart::Image(_) => None,
+            })
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+
+    /// Images attached to the message
+    pub fn images(&self) -> impl Iterator<Item = &ImageData> {
+        self.content.iter().filter_map(|part| match part {
+            ContentPart::Image(image) => Some(image),
+            ContentPart::Text { .. } => None,
+        })
+    }
+
+    /// Message with each image replaced by a short note, for resending earlier exchanges
+    /// without paying for the images again
+    pub fn without_images(mut self) -> Self {
+        for part in &mut self.content {
+            if let ContentPart::Image(image) = part {
+                *part = ContentPart::Text {
+                    text: format!("[{} image attached earlier]", image.media_type),
+                };
+            }
         }
+        self
+    }
+
+    /// Rough token count of the message, images included
+    pub fn estimated_tokens(&self) -> u64 {
+        estimated_text_tokens(&self.text())
+            + self.images().count() as u64 * LLMRequest::IMAGE_TOKENS
     }
 }
 
+/// Rough token count of a text (about 4 characters per token)
+pub(crate) fn estimated_text_tokens(text: &str) -> u64 {
+    text.chars().count().div_ceil(4) as u64
+}
+
 /// LLM Client Trait
 #[async_trait::async_trait]
 pub trait LLMClient: Send + Sync {
     /// Generate response from LLM
-    async fn generate(&self, request: LLMRequest) -> Result<String>;
+    async fn generate(&self, request: LLMRequest) -> Result<LLMResponse>;
+
+    /// Generate response from LLM, passing each delta to `on_delta` as it arrives.
+    /// Providers without streaming support deliver the whole answer as one delta.
+    async fn generate_stream(
+        &self,
+        request: LLMRequest,
+        on_delta: &mut (dyn for<'d> FnMut(StreamDelta<'d>) + Send),
+    ) -> Result<LLMResponse> {
+        let response = self.generate(request).await?;
+        if let Some(ref reasoning) = response.reasoning {
+            on_delta(StreamDelta::Reasoning(reasoning));
+        }
+        on_delta(StreamDelta::Text(&response.text));
+        Ok(response)
+    }
 }
 
-/// Concrete LLM Client enum to support different providers
-pub enum Client {
+/// Provider-specific client
+pub enum Backend {
     OpenAI(OpenAIClient),
     Anthropic(AnthropicClient),
+    Gemini(GeminiClient),
+    Ollama(OllamaClient),
+    /// Cassette recorder/player for offline runs
+    Replay(ReplayClient),
 }
 
-impl Client {
+impl Backend {
     /// Generate response from LLM
-    pub async fn generate(&self, request: LLMRequest) -> Result<String> {
+    pub(crate) async fn generate(&self, request: LLMRequest) -> Result<LLMResponse> {
+        match self {
+            Backend::OpenAI(client) => client.generate(request).await,
+            Backend::Anthropic(client) => client.generate(request).await,
+            Backend::Gemini(client) => client.generate(request).await,
+            Backend::Ollama(client) => client.generate(request).await,
+            Backend::Replay(client) => client.generate(request).await,
+        }
+    }
+
+    /// Generate response from LLM, streaming deltas to `on_delta`
+    pub(crate) async fn generate_stream(
+        &self,
+        request: LLMRequest,
+        on_delta: &mut (dyn FnMut(StreamDelta) + Send),
+    ) -> Result<LLMResponse> {
         match self {
-            Client::OpenAI(client) => client.generate(request).await,
-            Client::Anthropic(client) => client.generate(request).await,
+            Backend::OpenAI(client) => client.generate_stream(request, on_delta).await,
+            Backend::Anthropic(client) => client.generate_stream(request, on_delta).await,
+            Backend::Gemini(client) => client.generate_stream(request, on_delta).await,
+            Backend::Ollama(client) => client.generate_stream(request, on_delta).await,
+            Backend::Replay(client) => client.generate_stream(request, on_delta).await,
         }
     }
 
-    /// Generate response with parsing and retry logic (3 attempts for parsing failures only)
-    pub async fn generate_with_parser<T, P>(&self, request: LLMRequest, parser: P) -> Result<T>
+    /// List the models offered by the provider's API
+    pub(crate) async fn list_models(&self) -> Result<Vec<ModelInfo>> {
+        match self {
+            Backend::OpenAI(client) => client.list_models().await,
+            Backend::Anthropic(client) => client.list_models().await,
+            Backend::Ollama(client) => client.list_models().await,
+            Backend::Gemini(_) => anyhow::bail!("Listing Gemini models is not supported"),
+            Backend::Replay(_) => anyhow::bail!("Listing models is not supported in replay mode"),
+        }
+    }
+}
+
+/// Provider backend with the provider and model it serves
+struct Target {
+    provider: Provider,
+    model: String,
+    backend: Backend,
+}
+
+impl Target {
+    /// Display name, e.g. `Anthropic (claude-sonnet-4-5)`
+    fn label(&self) -> String {
+        format!("{} ({})", self.provider.as_display(), self.model)
+    }
+}
+
+/// Concrete LLM Client, retrying rate limits and transient server errors
+/// and falling back to the next configured provider/model when they persist
+pub struct Client {
+    /// Primary backend first, then the fallbacks in order
+    targets: Vec<Target>,
+    /// Index of the target in use; advances when a target fails over
+    active: AtomicUsize,
+    max_retries: u32,
+    budget: Option<BudgetConfig>,
+    on_retry: Option<RetryCallback>,
+}
+
+/// Callback notified before each retry
+type RetryCallback = Box<dyn Fn(&RetryNotice) + Send + Sync>;
+
+impl Client {
+    /// Create a client for `provider`/`model` that retries up to `max_retries` times
+    pub fn new(backend: Backend, provider: Provider, model: String, max_retries: u32) -> Self {
+        Self {
+            targets: vec![Target {
+                provider,
+                model,
+                backend,
+            }],
+            active: AtomicUsize::new(0),
+            max_retries,
+            budget: None,
+            on_retry: None,
+        }
+    }
+
+    /// Add a backend to try when the previous ones fail with a network,
+    /// rate limit or server error
+    pub fn fallback(mut self, backend: Backend, provider: Provider, model: String) -> Self {
+        self.targets.push(Target {
+            provider,
+            model,
+            backend,
+        });
+        self
+    }
+
+    /// Enforce spending limits and warn about large inputs before each request
+    pub fn budget(mut self, budget: BudgetConfig) -> Self {
+        self.budget = Some(budget);
+        self
+    }
+
+    /// Call `on_retry` before waiting for each retry (e.g., to update a spinner)
+    pub fn on_retry(mut self, on_retry: impl Fn(&RetryNotice) + Send + Sync + 'static) -> Self {
+        self.on_retry = Some(Box::new(on_retry));
+        self
+    }
+
+    /// Fallback backend in use after the primary failed, e.g. `Anthropic (claude-sonnet-4-5)`
+    pub fn fallback_in_use(&self) -> Option<String> {
+        let active = self.active.load(Ordering::Relaxed);
+        (active > 0).then(|| self.targets[active].label())
+    }
+
+    /// Wait before retry number `retry`, or give back `error` if it should not be retried
+    async fn wait_for_retry(&self, error: anyhow::Error, retry: u32) -> Result<()> {
+        let Some(notice) = plan_retry(&error, retry, self.max_retries) else {
+            return Err(error);
+        };
+
+        tracing::warn!("{} ({})", notice, error);
+        if let Some(ref on_retry) = self.on_retry {
+            on_retry(&notice);
+        }
+        tokio::time::sleep(notice.delay).await;
+
+        Ok(())
+    }
+
+    /// Switch to the target after `index`, or give back `error` if there is none
+    /// or the error would not be fixed by another provider (e.g., a bad API key)
+    fn fall_back(&self, error: anyhow::Error, index: usize) -> Result<usize> {
+        let eligible = error
+            .downcast_ref::<LLMError>()
+            .is_some_and(LLMError::allows_fallback);
+        if !eligible || index + 1 >= self.targets.len() {
+            return Err(error);
+        }
+
+        let next = index + 1;
+        tracing::warn!(
+            "{} failed, falling back to {} ({})",
+            self.targets[index].label(),
+            self.targets[next].label(),
+            error
+        );
+        self.active.store(next, Ordering::Relaxed);
+
+        Ok(next)
+    }
+
+    /// Check the spending limits and the input size before sending a request
+    fn preflight(&self, request: &LLMRequest) -> Result<()> {
+        let Some(ref budget) = self.budget else {
+            return Ok(());
+        };
+
+        let target = &self.targets[self.active.load(Ordering::Relaxed)];
+        let provider = &target.provider;
+        check_budget(provider, &target.model, budget)?;
+
+        let input_tokens = request.estimated_input_tokens();
+        if input_tokens > budget.warn_input_tokens {
+            tracing::warn!(
+                "Large request: about {} input tokens (budget.warn_input_tokens = {})",
+                input_tokens,
+                budget.warn_input_tokens
+            );
+            eprintln!(
+                "⚠️  Large request: about {} input tokens will be sent to {} (budget.warn_input_tokens = {})",
+                input_tokens,
+                provider.as_display(),
+                budget.warn_input_tokens
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Append the token usage of a response to the usage ledger.
+    /// Failing to record usage does not fail the request.
+    fn record_usage(&self, target: &Target, mode: LLMMode, response: &LLMResponse) {
+        let Some(usage) = response.usage else {
+            return;
+        };
+
+        tracing::debug!(
+            "Token usage: {} input ({} cache hits, {} cache writes), {} output",
+            usage.input_tokens,
+            usage.cached_tokens,
+            usage.cache_write_tokens,
+            usage.output_tokens
+        );
+        let record = UsageRecord::now(target.provider.clone(), target.model.clone(), mode, usage);
+        if let Err(e) = append_usage(&record) {
+            tracing::warn!("Failed to record token usage: {}", e);
+        }
+    }
+
+    /// Generate response from LLM
+    pub async fn generate(&self, request: LLMRequest) -> Result<LLMResponse> {
+        self.preflight(&request)?;
+
+        let mut index = self.active.load(Ordering::Relaxed);
+        loop {
+            let target = &self.targets[index];
+            match self.generate_with(target, &request).await {
+                Ok(response) => {
+                    self.record_usage(target, request.mode, &response);
+                    return Ok(response);
+                }
+                Err(e) => index = self.fall_back(e, index)?,
+            }
+        }
+    }
+
+    /// Generate response from one target, retrying transient failures
+    async fn generate_with(&self, target: &Target, request: &LLMRequest) -> Result<LLMResponse> {
+        let mut retry = 0;
+        loop {
+            match target.backend.generate(request.clone()).await {
+                Ok(response) => return Ok(response),
+                Err(e) => {
+                    retry += 1;
+                    self.wait_for_retry(e, retry).await?;
+                }
+            }
+        }
+    }
+
+    /// Generate response from LLM, streaming deltas to `on_delta`.
+    /// Failures after the first delta are neither retried nor passed to a fallback.
+    pub async fn generate_stream(
+        &self,
+        request: LLMRequest,
+        on_delta: &mut (dyn FnMut(StreamDelta) + Send),
+    ) -> Result<LLMResponse> {
+        self.preflight(&request)?;
+
+        let mut index = self.active.load(Ordering::Relaxed);
+        loop {
+            let target = &self.targets[index];
+            let mut streamed = false;
+            match self
+                .generate_stream_with(target, &request, on_delta, &mut streamed)
+                .await
+            {
+                Ok(response) => {
+                    self.record_usage(target, request.mode, &response);
+                    return Ok(response);
+                }
+                Err(e) if streamed => return Err(e),
+                Err(e) => index = self.fall_back(e, index)?,
+            }
+        }
+    }
+
+    /// Stream a response from one target, retrying transient failures until
+    /// the first delta arrives (`streamed` is set from then on)
+    async fn generate_stream_with(
+        &self,
+        target: &Target,
+        request: &LLMRequest,
+        on_delta: &mut (dyn FnMut(StreamDelta) + Send),
+        streamed: &mut bool,
+    ) -> Result<LLMResponse> {
+        let mut retry = 0;
+        loop {
+            let result = target
+                .backend
+                .generate_stream(request.clone(), &mut |delta| {
+                    *streamed = true;
+                    on_delta(delta);
+                })
+                .await;
+
+            match result {
+                Ok(response) => return Ok(response),
+                Err(e) if *streamed => return Err(e),
+                Err(e) => {
+                    retry += 1;
+                    self.wait_for_retry(e, retry).await?;
+                }
+            }
+        }
+    }
+
+    /// Generate response and parse it. When parsing fails, the invalid output and the
+    /// parse error are sent back so the model can repair its answer (3 attempts in total)
+    pub async fn generate_with_parser<T, P>(&self, mut request: LLMRequest, parser: P) -> Result<T>
     where
         P: Fn(&str) -> Result<T>,
     {
-        const MAX_RETRIES: u32 = 3;
+        const MAX_ATTEMPTS: u32 = 3;
 
-        // Call LLM once - if this fails, return immediately
-        let response = self.generate(request).await?;
+        let mut attempt = 1;
+        loop {
+            // Request failures are returned immediately (transient ones are retried by generate)
+            let response = self.generate(request.clone()).await?.text;
 
-        // Retry parsing up to MAX_RETRIES times
-        for attempt in 1..=MAX_RETRIES {
-            match parser(&response) {
+            let error = match parser(&response) {
                 Ok(parsed) => return Ok(parsed),
-                Err(e) => {
-                    if attempt < MAX_RETRIES {
-                        tracing::warn!(
-                            "Parse failed (attempt {}/{}): Retrying...",
-                            attempt,
-                            MAX_RETRIES
-                        );
-                        continue;
-                    } else {
-                        tracing::error!("All parse retry attempts exhausted.");
-                        return Err(e);
-                    }
-                }
+                Err(e) => e,
+            };
+
+            tracing::debug!(
+                "Parse failed (attempt {}/{}): {:#}\nRaw response:\n{}",
+                attempt,
+                MAX_ATTEMPTS,
+                error,
+                response
+            );
+
+            if attempt >= MAX_ATTEMPTS {
+                tracing::error!("All parse attempts exhausted.");
+                return Err(error);
             }
-        }
 
-        unreachable!()
+            // Ask the model to fix its previous output
+            request.messages.push(LLMMessage::assistant(response));
+            request.messages.push(LLMMessage::user(format!(
+                "Your previous response could not be parsed: {:#}\n\
+                 Respond again with only the JSON object in the required format.",
+                error
+            )));
+            attempt += 1;
+        }
     }
 }
 
 /// Verify LLM configuration without creating a persistent client
-pub async fn verify_client(config: &LLMConfig) -> Result<bool> {
-    let client = create_client(config)?;
+pub async fn verify_client(config: &LLMConfig, network: &NetworkConfig) -> Result<bool> {
+    // Only the configured provider is checked, never a fallback
+    let client = Client::new(
+        create_backend(config, network)?,
+        config.provider.clone(),
+        config.model.clone(),
+        config.max_retries,
+    );
     let request = LLMRequest {
-        system: "This is a test, please respond shortly.".to_string(),
+        mode: LLMMode::Verify,
+        system: "This is a test, please respond shortly.".into(),
         messages: vec![LLMMessage::user("Hello")],
+        response_schema: None,
+        params: GenerationParams::default(),
     };
 
     match client.generate(request).await {
@@ -116,32 +803,171 @@ pub async fn verify_client(config: &LLMConfig) -> Result<bool> {
     }
 }
 
-/// Create LLM client based on configuration
-pub fn create_client(config: &LLMConfig) -> Result<Client> {
-    match config.provider {
+/// Load the provider's secret, reporting a missing or unreadable one as an authentication error
+fn load_secret<T: ProviderSecret>(provider: &Provider) -> Result<T> {
+    SecretManager::load(provider).map_err(|e| {
+        LLMError::Auth {
+            provider: provider.as_display(),
+            message: format!("failed to load secret: {}", e),
+        }
+        .into()
+    })
+}
+
+/// Create LLM client based on configuration, with the fallbacks from `llm.fallbacks`.
+/// A fallback that cannot be set up (e.g., no API key stored) is skipped.
+pub fn create_client(config: &LLMConfig, network: &NetworkConfig) -> Result<Client> {
+    let backend = create_recorded_backend(config, network)?;
+    let mut client = Client::new(
+        backend,
+        config.provider.clone(),
+        config.model.clone(),
+        config.max_retries,
+    );
+
+    for fallback in &config.fallbacks {
+        let fallback_config = LLMConfig {
+            provider: fallback.provider.clone(),
+            model: fallback.model.clone(),
+            ..config.clone()
+        };
+        match create_recorded_backend(&fallback_config, network) {
+            Ok(backend) => {
+                client = client.fallback(backend, fallback.provider.clone(), fallback.model.clone())
+            }
+            Err(e) => tracing::warn!(
+                "Skipping fallback {} ({}): {}",
+                fallback.provider.as_display(),
+                fallback.model,
+                e
+            ),
+        }
+    }
+
+    Ok(client)
+}
+
+/// Create the backend for the configured provider, wrapped in a recorder when
+/// `llm.replay` (or `DOUM_REPLAY`) is `record`, or a player without any provider when `replay`
+fn create_recorded_backend(config: &LLMConfig, network: &NetworkConfig) -> Result<Backend> {
+    match config.replay.mode()? {
+        ReplayMode::Off => create_backend(config, network),
+        ReplayMode::Record => Ok(Backend::Replay(ReplayClient::record(
+            create_backend(config, network)?,
+            config.replay.dir()?,
+        ))),
+        ReplayMode::Replay => Ok(Backend::Replay(ReplayClient::replay(config.replay.dir()?))),
+    }
+}
+
+/// Create the provider-specific client for the configured provider
+pub(crate) fn create_backend(config: &LLMConfig, network: &NetworkConfig) -> Result<Backend> {
+    match &config.provider {
         Provider::OpenAI => {
-            let secret: OpenAISecret =
-                SecretManager::load(&config.provider).context("Failed to load OpenAI secret")?;
+            let secret: OpenAISecret = load_secret(&config.provider)?;
 
             let openai_config = OpenAIConfig {
+                name: config.provider.as_display(),
                 model: config.model.clone(),
                 api_key: secret.api_key,
                 organization: secret.organization,
                 project: secret.project,
+                base_url: OpenAIClient::DEFAULT_BASE_URL.to_string(),
+                api: OpenAIApi::Responses,
+                auth: OpenAIAuth::Bearer,
+                api_version: None,
+                use_thinking: config.use_thinking,
+                use_web_search: config.use_web_search,
+                structured_outputs: true,
             };
-            let client = OpenAIClient::new(openai_config, config.timeout)?;
-            Ok(Client::OpenAI(client))
+            let client = OpenAIClient::new(openai_config, config.timeout, network)?;
+            Ok(Backend::OpenAI(client))
         }
         Provider::Anthropic => {
-            let secret: AnthropicSecret =
-                SecretManager::load(&config.provider).context("Failed to load Anthropic secret")?;
+            let secret: AnthropicSecret = load_secret(&config.provider)?;
 
             let anthropic_config = AnthropicConfig {
                 model: config.model.clone(),
                 api_key: secret.api_key,
+                use_thinking: config.use_thinking,
+                use_web_search: config.use_web_search,
+            };
+            let client = AnthropicClient::new(anthropic_config, config.timeout, network)?;
+            Ok(Backend::Anthropic(client))
+        }
+        Provider::AzureOpenAI => {
+            let secret: AzureOpenAISecret = load_secret(&config.provider)?;
+
+            // On Azure the model name is the deployment name
+            let openai_config = OpenAIConfig {
+                name: config.provider.as_display(),
+                model: config.model.clone(),
+                api_key: secret.api_key,
+                organization: None,
+                project: None,
+                base_url: format!(
+                    "{}/openai/deployments/{}",
+                    secret.endpoint.trim_end_matches('/'),
+                    config.model
+                ),
+                api: OpenAIApi::ChatCompletions,
+                auth: OpenAIAuth::ApiKey,
+                api_version: Some(secret.api_version),
+                use_thinking: config.use_thinking,
+                use_web_search: config.use_web_search,
+                structured_outputs: true,
+            };
+            let client = OpenAIClient::new(openai_config, config.timeout, network)?;
+            Ok(Backend::OpenAI(client))
+        }
+        Provider::Gemini => {
+            let secret: GeminiSecret = load_secret(&config.provider)?;
+
+            let gemini_config = GeminiConfig {
+                model: config.model.clone(),
+                api_key: secret.api_key,
+            };
+            let client = GeminiClient::new(gemini_config, config.timeout, network)?;
+            Ok(Backend::Gemini(client))
+        }
+        Provider::Ollama => {
+            let ollama_config = OllamaConfig {
+                model: config.model.clone(),
+                base_url: config.ollama_url.clone(),
+            };
+            let client = OllamaClient::new(ollama_config, config.timeout, network)?;
+            Ok(Backend::Ollama(client))
+        }
+        Provider::Custom(name) => {
+            let custom = config
+                .providers
+                .get(name)
+                .with_context(|| Provider::unknown(name, config))?;
+
+            let api_key = if custom.requires_secret() {
+                let secret: CustomSecret = load_secret(&config.provider)?;
+                secret.api_key
+            } else {
+                String::new()
+            };
+
+            let openai_config = OpenAIConfig {
+                name: name.clone(),
+                model: config.model.clone(),
+                api_key,
+                organization: None,
+                project: None,
+                base_url: custom.base_url.clone(),
+                api: custom.api,
+                auth: custom.auth,
+                api_version: None,
+                use_thinking: config.use_thinking,
+                // OpenAI's hosted web search tool, which compatible servers rarely offer
+                use_web_search: config.use_web_search && custom.web_search,
+                structured_outputs: custom.structured_outputs,
             };
-            let client = AnthropicClient::new(anthropic_config, config.timeout)?;
-            Ok(Client::Anthropic(client))
+            let client = OpenAIClient::new(openai_config, config.timeout, network)?;
+            Ok(Backend::OpenAI(client))
         }
     }
 }
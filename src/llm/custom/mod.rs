@@ -0,0 +1,10 @@
+//! User-defined OpenAI-compatible providers.
+//!
+//! Custom providers are declared under `[llm.providers.<name>]` in `config.toml`
+//! and are served by [`OpenAIClient`](crate::llm::OpenAIClient).
+
+pub mod payloads;
+pub mod secret;
+
+pub use payloads::CustomProviderConfig;
+pub use secret::CustomSecret;
@@ -0,0 +1,37 @@
+use crate::llm::openai::{OpenAIApi, OpenAIAuth};
+use serde::{Deserialize, Serialize};
+
+/// Definition of a user-defined OpenAI-compatible provider
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustomProviderConfig {
+    /// Base URL including the version segment (e.g., http://localhost:8000/v1)
+    pub base_url: String,
+    #[serde(default)]
+    pub auth: OpenAIAuth,
+    #[serde(default)]
+    pub api: OpenAIApi,
+    /// Models offered in `doum switch`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub models: Vec<String>,
+    /// Send the response schema as a strict `json_schema` format; when off, the
+    /// JSON answer is extracted from plain text for servers without the feature
+    #[serde(default = "default_structured_outputs")]
+    pub structured_outputs: bool,
+    /// The server offers OpenAI's `web_search` tool; used when `llm.use_web_search` is on
+    #[serde(default)]
+    pub web_search: bool,
+    /// The models take image attachments (`ask --image`)
+    #[serde(default)]
+    pub vision: bool,
+}
+
+fn default_structured_outputs() -> bool {
+    true
+}
+
+impl CustomProviderConfig {
+    /// Whether an API key must be stored for this provider
+    pub fn requires_secret(&self) -> bool {
+        self.auth != OpenAIAuth::None
+    }
+}
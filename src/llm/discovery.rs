@@ -0,0 +1,132 @@
+use crate::llm::Provider;
+use crate::llm::client::create_backend;
+use crate::llm::presets::ModelInfo;
+use crate::system::{LLMConfig, NetworkConfig, get_cache_dir};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use time::{Duration, OffsetDateTime};
+
+/// How long listed models are reused before the provider is asked again
+const CACHE_TTL: Duration = Duration::hours(24);
+
+/// OpenAI models that cannot answer text prompts (audio, images, embeddings, ...)
+const OPENAI_NON_CHAT: [&str; 9] = [
+    "audio",
+    "realtime",
+    "tts",
+    "transcribe",
+    "image",
+    "search",
+    "embedding",
+    "instruct",
+    "moderation",
+];
+
+/// Listed models per provider, stored in the cache directory
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ModelCache {
+    #[serde(default)]
+    providers: BTreeMap<String, CachedModels>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CachedModels {
+    #[serde(with = "time::serde::rfc3339")]
+    fetched_at: OffsetDateTime,
+    models: Vec<ModelInfo>,
+}
+
+/// Whether the provider's API can list its models
+pub fn supports_discovery(provider: &Provider) -> bool {
+    matches!(provider, Provider::OpenAI | Provider::Anthropic)
+}
+
+/// Models listed by the provider's API (`/v1/models`).
+/// A cached list younger than the TTL is reused unless `refresh` is set.
+pub async fn discover_models(
+    config: &LLMConfig,
+    network: &NetworkConfig,
+    provider: &Provider,
+    refresh: bool,
+) -> Result<Vec<ModelInfo>> {
+    let now = OffsetDateTime::now_utc();
+    let mut cache = load_cache();
+
+    if !refresh
+        && let Some(cached) = cache.providers.get(provider.as_str())
+        && now - cached.fetched_at < CACHE_TTL
+    {
+        return Ok(cached.models.clone());
+    }
+
+    let provider_config = LLMConfig {
+        provider: provider.clone(),
+        ..config.clone()
+    };
+    let mut models = create_backend(&provider_config, network)?
+        .list_models()
+        .await?;
+    if *provider == Provider::OpenAI {
+        models.retain(|m| is_openai_chat_model(&m.id));
+    }
+
+    cache.providers.insert(
+        provider.as_str().to_string(),
+        CachedModels {
+            fetched_at: now,
+            models: models.clone(),
+        },
+    );
+    if let Err(e) = save_cache(&cache) {
+        tracing::warn!("Failed to save model cache: {}", e);
+    }
+
+    Ok(models)
+}
+
+/// Presets first (with descriptions and prices), then the listed models missing from them
+pub fn merge_models(presets: Vec<ModelInfo>, listed: Vec<ModelInfo>) -> Vec<ModelInfo> {
+    let mut models = presets;
+    for model in listed {
+        if !models.iter().any(|m| m.id == model.id) {
+            models.push(model);
+        }
+    }
+    models
+}
+
+/// GPT and o-series text models
+fn is_openai_chat_model(id: &str) -> bool {
+    let family = ["gpt-", "chatgpt-", "o1", "o3", "o4"]
+        .iter()
+        .any(|prefix| id.starts_with(prefix));
+    family && !OPENAI_NON_CHAT.iter().any(|kind| id.contains(kind))
+}
+
+fn cache_path() -> Result<PathBuf> {
+    Ok(get_cache_dir()?.join("models.json"))
+}
+
+/// Load the model cache, treating a missing or unreadable one as empty
+fn load_cache() -> ModelCache {
+    cache_path()
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_cache(cache: &ModelCache) -> Result<()> {
+    let path = cache_path()?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).context("Failed to create cache directory")?;
+    }
+
+    let content = serde_json::to_string_pretty(cache).context("Failed to serialize model cache")?;
+    fs::write(&path, content).context("Failed to write model cache")?;
+
+    Ok(())
+}
@@ -0,0 +1,439 @@
+use reqwest::StatusCode;
+use std::time::Duration;
+use thiserror::Error;
+
+/// Failure while talking to an LLM provider
+#[derive(Debug, Error)]
+pub enum LLMError {
+    /// Missing, invalid or unauthorized credentials
+    #[error("{provider} authentication failed: {message}")]
+    Auth { provider: String, message: String },
+
+    /// Too many requests or tokens in the current window
+    #[error("{provider} rate limit reached: {message}")]
+    RateLimited {
+        provider: String,
+        message: String,
+        /// Wait requested by the server before retrying
+        retry_after: Option<Duration>,
+    },
+
+    /// No response (or no streamed data) within `llm.timeout`
+    #[error("{provider} request timed out: {message}")]
+    Timeout { provider: String, message: String },
+
+    /// Input does not fit in the model's context window
+    #[error("{provider} context window exceeded: {message}")]
+    ContextTooLong { provider: String, message: String },
+
+    /// Unknown model or deployment
+    #[error("{provider} model not found: {message}")]
+    ModelNotFound { provider: String, message: String },
+
+    /// Connection could not be established or was interrupted
+    #[error("Failed to reach {provider}: {message}")]
+    Network { provider: String, message: String },
+
+    /// Response could not be understood
+    #[error("Invalid {provider} response: {message}")]
+    BadResponse { provider: String, message: String },
+
+    /// Provider-side failure (5xx, overloaded)
+    #[error("{provider} server error ({status}): {message}")]
+    Server {
+        provider: String,
+        status: StatusCode,
+        message: String,
+        /// Wait requested by the server before retrying
+        retry_after: Option<Duration>,
+    },
+
+    /// Any other error status
+    #[error("{provider} API Error ({status}): {message}")]
+    Api {
+        provider: String,
+        status: StatusCode,
+        message: String,
+    },
+
+    /// Request refused locally because a spending limit is reached,
+    /// or cannot be checked for a model without a price
+    #[error("{provider} request refused by the spending budget: {message}")]
+    BudgetExceeded { provider: String, message: String },
+}
+
+impl LLMError {
+    /// Classify an error status using the provider's error type (or code) and message
+    pub fn from_status(
+        provider: &str,
+        status: StatusCode,
+        error_type: Option<&str>,
+        message: String,
+        retry_after: Option<Duration>,
+    ) -> Self {
+        // Exhausted credit is reported as 429 but will not recover by retrying
+        if error_type == Some("insufficient_quota") {
+            return LLMError::Api {
+                provider: provider.to_string(),
+                status,
+                message,
+            };
+        }
+
+        if let Some(error) = Self::classify(provider, error_type, &message, retry_after) {
+            return error;
+        }
+
+        let provider = provider.to_string();
+        match status.as_u16() {
+            401 | 403 => LLMError::Auth { provider, message },
+            404 => LLMError::ModelNotFound { provider, message },
+            408 | 504 => LLMError::Timeout { provider, message },
+            413 => LLMError::ContextTooLong { provider, message },
+            429 => LLMError::RateLimited {
+                provider,
+                message,
+                retry_after,
+            },
+            500..=599 => LLMError::Server {
+                provider,
+                status,
+                message,
+                retry_after,
+            },
+            _ => LLMError::Api {
+                provider,
+                status,
+                message,
+            },
+        }
+    }
+
+    /// Classify an error reported inside a response stream (no HTTP status)
+    pub fn from_event(provider: &str, error_type: Option<&str>, message: String) -> Self {
+        Self::classify(provider, error_type, &message, None).unwrap_or(LLMError::BadResponse {
+            provider: provider.to_string(),
+            message,
+        })
+    }
+
+    /// Classify a transport error from reqwest
+    pub fn from_reqwest(provider: &str, error: reqwest::Error) -> Self {
+        let provider = provider.to_string();
+        if error.is_timeout() {
+            LLMError::Timeout {
+                provider,
+                message: "no response received in time".to_string(),
+            }
+        } else if error.is_decode() {
+            LLMError::BadResponse {
+                provider,
+                message: error.to_string(),
+            }
+        } else {
+            LLMError::Network {
+                provider,
+                message: error.to_string(),
+            }
+        }
+    }
+
+    /// Map the OpenAI, Anthropic and Gemini error types/codes (and well-known messages)
+    fn classify(
+        provider: &str,
+        error_type: Option<&str>,
+        message: &str,
+        retry_after: Option<Duration>,
+    ) -> Option<Self> {
+        let lower = message.to_lowercase();
+        let context_too_long = ["context length", "context window", "prompt is too long"]
+            .iter()
+            .any(|pattern| lower.contains(pattern));
+        // Gemini reports a bad key as INVALID_ARGUMENT
+        let invalid_key = lower.contains("api key not valid");
+
+        let provider = provider.to_string();
+        let message = message.to_string();
+        let error = match error_type.unwrap_or_default().to_lowercase().as_str() {
+            _ if context_too_long => LLMError::ContextTooLong { provider, message },
+            _ if invalid_key => LLMError::Auth { provider, message },
+            "context_length_exceeded" | "request_too_large" => {
+                LLMError::ContextTooLong { provider, message }
+            }
+            "authentication_error"
+            | "permission_error"
+            | "invalid_api_key"
+            | "unauthenticated"
+            | "permission_denied" => LLMError::Auth { provider, message },
+            "not_found_error" | "model_not_found" | "deployment_not_found" | "not_found" => {
+                LLMError::ModelNotFound { provider, message }
+            }
+            "rate_limit_error" | "rate_limit_exceeded" | "resource_exhausted" => {
+                LLMError::RateLimited {
+                    provider,
+                    message,
+                    retry_after,
+                }
+            }
+            "overloaded_error" | "unavailable" => LLMError::Server {
+                provider,
+                status: StatusCode::SERVICE_UNAVAILABLE,
+                message,
+                retry_after,
+            },
+            "api_error" | "server_error" | "internal" => LLMError::Server {
+                provider,
+                status: StatusCode::INTERNAL_SERVER_ERROR,
+                message,
+                retry_after,
+            },
+            "deadline_exceeded" => LLMError::Timeout { provider, message },
+            _ => return None,
+        };
+
+        Some(error)
+    }
+
+    /// Whether the request may succeed when sent again (rate limits and transient server errors)
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            LLMError::RateLimited { .. } => true,
+            LLMError::Server { status, .. } => matches!(status.as_u16(), 500 | 502 | 503 | 529),
+            _ => false,
+        }
+    }
+
+    /// Whether another provider may succeed where this one failed
+    /// (network trouble, rate limits and server errors, but not bad credentials)
+    pub fn allows_fallback(&self) -> bool {
+        matches!(
+            self,
+            LLMError::Network { .. }
+                | LLMError::Timeout { .. }
+                | LLMError::RateLimited { .. }
+                | LLMError::Server { .. }
+        )
+    }
+
+    /// Wait requested by the server before retrying
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            LLMError::RateLimited { retry_after, .. } | LLMError::Server { retry_after, .. } => {
+                *retry_after
+            }
+            _ => None,
+        }
+    }
+
+    /// Name of the provider that failed
+    pub fn provider(&self) -> &str {
+        match self {
+            LLMError::Auth { provider, .. }
+            | LLMError::RateLimited { provider, .. }
+            | LLMError::Timeout { provider, .. }
+            | LLMError::ContextTooLong { provider, .. }
+            | LLMError::ModelNotFound { provider, .. }
+            | LLMError::Network { provider, .. }
+            | LLMError::BadResponse { provider, .. }
+            | LLMError::Server { provider, .. }
+            | LLMError::Api { provider, .. }
+            | LLMError::BudgetExceeded { provider, .. } => provider,
+        }
+    }
+
+    /// What the user can do about the error
+    pub fn hint(&self) -> &'static str {
+        match self {
+            LLMError::Auth { .. } => "Check the API key with `doum secret`.",
+            LLMError::RateLimited { .. } => {
+                "Wait a moment and try again, or raise `llm.max_retries`."
+            }
+            LLMError::Timeout { .. } => {
+                "Increase the timeout with `doum config set llm.timeout <seconds>` (or `modes.<mode>.timeout`)."
+            }
+            LLMError::ContextTooLong { .. } => {
+                "Shorten the input, or pick a model with a larger context window with `doum switch`."
+            }
+            LLMError::ModelNotFound { .. } => "Pick an available model with `doum switch`.",
+            LLMError::Network { .. } => "Check the network connection and the provider URL.",
+            LLMError::BadResponse { .. } => {
+                "Try again; if it keeps happening, pick another model with `doum switch`."
+            }
+            LLMError::Server { .. } => "The provider is having trouble; try again later.",
+            LLMError::Api { .. } => "Check the provider settings with `doum config show`.",
+            LLMError::BudgetExceeded { .. } => {
+                "See `doum usage`, or raise the limit with `doum config set budget.daily_usd <usd>` (or `budget.monthly_usd`). For models without preset prices, set `budget.default_input_price` and `budget.default_output_price` (USD per million tokens)."
+            }
+        }
+    }
+
+    /// Process exit code, so scripts can tell failures apart
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            LLMError::Auth { .. } => 64,
+            LLMError::RateLimited { .. } => 65,
+            LLMError::Timeout { .. } => 66,
+            LLMError::ContextTooLong { .. } => 67,
+            LLMError::ModelNotFound { .. } => 68,
+            LLMError::Network { .. } => 69,
+            LLMError::BadResponse { .. } => 70,
+            LLMError::Server { .. } => 71,
+            LLMError::Api { .. } => 72,
+            LLMError::BudgetExceeded { .. } => 73,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn from_status(status: u16, error_type: Option<&str>, message: &str) -> LLMError {
+        LLMError::from_status(
+            "OpenAI",
+            StatusCode::from_u16(status).unwrap(),
+            error_type,
+            message.to_string(),
+            Some(Duration::from_secs(2)),
+        )
+    }
+
+    #[test]
+    fn insufficient_quota_is_not_retryable() {
+        let error = from_status(429, Some("insufficient_quota"), "You exceeded your quota");
+
+        assert!(matches!(error, LLMError::Api { .. }));
+        assert!(!error.is_retryable());
+        assert!(!error.allows_fallback());
+    }
+
+    #[test]
+    fn falls_back_to_status_without_known_type() {
+        assert!(matches!(
+            from_status(401, None, "nope"),
+            LLMError::Auth { .. }
+        ));
+        assert!(matches!(
+            from_status(404, None, "nope"),
+            LLMError::ModelNotFound { .. }
+        ));
+        assert!(matches!(
+            from_status(413, None, "nope"),
+            LLMError::ContextTooLong { .. }
+        ));
+        assert!(matches!(
+            from_status(504, None, "nope"),
+            LLMError::Timeout { .. }
+        ));
+        assert!(matches!(
+            from_status(418, Some("teapot"), "nope"),
+            LLMError::Api { .. }
+        ));
+
+        let rate_limited = from_status(429, None, "slow down");
+        assert!(rate_limited.is_retryable());
+        assert_eq!(rate_limited.retry_after(), Some(Duration::from_secs(2)));
+
+        let server = from_status(502, None, "bad gateway");
+        assert!(server.is_retryable());
+        assert!(!from_status(501, None, "not implemented").is_retryable());
+    }
+
+    #[test]
+    fn error_type_wins_over_status() {
+        // Anthropic reports an overloaded API as 529 with overloaded_error, Gemini a bad model as 400
+        let overloaded = from_status(529, Some("overloaded_error"), "Overloaded");
+        assert!(matches!(
+            overloaded,
+            LLMError::Server { status, .. } if status == StatusCode::SERVICE_UNAVAILABLE
+        ));
+        assert!(matches!(
+            from_status(400, Some("NOT_FOUND"), "models/foo is not found"),
+            LLMError::ModelNotFound { .. }
+        ));
+        assert!(matches!(
+            from_status(400, Some("RESOURCE_EXHAUSTED"), "quota"),
+            LLMError::RateLimited { .. }
+        ));
+    }
+
+    #[test]
+    fn message_wins_over_error_type_and_status() {
+        // Gemini reports a bad key as INVALID_ARGUMENT with status 400
+        assert!(matches!(
+            from_status(
+                400,
+                Some("INVALID_ARGUMENT"),
+                "API key not valid. Please pass a valid API key."
+            ),
+            LLMError::Auth { .. }
+        ));
+        // A context overflow reported under a generic type is still a context error
+        assert!(matches!(
+            from_status(
+                400,
+                Some("invalid_request_error"),
+                "prompt is too long: 210000 tokens > 200000 maximum"
+            ),
+            LLMError::ContextTooLong { .. }
+        ));
+        assert!(matches!(
+            from_status(
+                500,
+                Some("server_error"),
+                "This model's maximum context length is 8192"
+            ),
+            LLMError::ContextTooLong { .. }
+        ));
+    }
+
+    #[test]
+    fn stream_errors_default_to_bad_response() {
+        assert!(matches!(
+            LLMError::from_event(
+                "Anthropic",
+                Some("overloaded_error"),
+                "Overloaded".to_string()
+            ),
+            LLMError::Server { .. }
+        ));
+        assert!(matches!(
+            LLMError::from_event("Anthropic", Some("something_new"), "?".to_string()),
+            LLMError::BadResponse { .. }
+        ));
+        assert!(matches!(
+            LLMError::from_event("Anthropic", None, "?".to_string()),
+            LLMError::BadResponse { .. }
+        ));
+    }
+
+    #[test]
+    fn exit_codes_are_distinct() {
+        let errors = [
+            from_status(401, None, ""),
+            from_status(429, None, ""),
+            from_status(504, None, ""),
+            from_status(413, None, ""),
+            from_status(404, None, ""),
+            LLMError::Network {
+                provider: "OpenAI".to_string(),
+                message: String::new(),
+            },
+            LLMError::BadResponse {
+                provider: "OpenAI".to_string(),
+                message: String::new(),
+            },
+            from_status(500, None, ""),
+            from_status(418, None, ""),
+            LLMError::BudgetExceeded {
+                provider: "OpenAI".to_string(),
+                message: String::new(),
+            },
+        ];
+
+        let mut codes: Vec<i32> = errors.iter().map(LLMError::exit_code).collect();
+        codes.sort();
+        codes.dedup();
+        assert_eq!(codes.len(), errors.len());
+    }
+}
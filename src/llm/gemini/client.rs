@@ -0,0 +1,188 @@
+use crate::llm::LLMRole;
+use crate::llm::client::{ContentPart, LLMClient, LLMRequest, LLMResponse, Usage};
+use crate::llm::error::LLMError;
+use crate::llm::gemini::payloads::{
+    GeminiConfig, GeminiContent, GeminiError, GeminiGenerationConfig, GeminiInlineData, GeminiPart,
+    GeminiRequest, GeminiResponse, GeminiThinkingConfig,
+};
+use crate::llm::http::client_builder;
+use crate::llm::retry::retry_after;
+use crate::system::NetworkConfig;
+use anyhow::{Context, Result};
+use reqwest::Client;
+use std::time::Duration;
+
+/// Gemini LLM Client
+pub struct GeminiClient {
+    http_client: Client,
+    config: GeminiConfig,
+}
+
+impl GeminiClient {
+    /// Provider name used in errors
+    const PROVIDER: &'static str = "Gemini";
+
+    /// Gemini API base URL
+    const API_URL: &'static str = "https://generativelanguage.googleapis.com/v1beta/models";
+
+    /// Create a new GeminiClient
+    pub fn new(config: GeminiConfig, timeout: u64, network: &NetworkConfig) -> Result<Self> {
+        if config.api_key.is_empty() {
+            return Err(LLMError::Auth {
+                provider: Self::PROVIDER.to_string(),
+                message: "API key is not set".to_string(),
+            }
+            .into());
+        }
+
+        let http_client = client_builder(network)?
+            .timeout(Duration::from_secs(timeout))
+            .build()
+            .context("Failed to build HTTP client")?;
+
+        Ok(Self {
+            http_client,
+            config,
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl LLMClient for GeminiClient {
+    async fn generate(&self, request: LLMRequest) -> Result<LLMResponse> {
+        let params = request.params;
+        let contents = request
+            .messages
+            .into_iter()
+            .map(|m| GeminiContent {
+                role: Some(
+                    match m.role {
+                        LLMRole::User => "user",
+                        LLMRole::Assistant => "model",
+                    }
+                    .to_string(),
+                ),
+                parts: m
+                    .content
+                    .into_iter()
+                    .map(|part| match part {
+                        ContentPart::Text { text } => GeminiPart {
+                            text,
+                            ..Default::default()
+                        },
+                        ContentPart::Image(image) => GeminiPart {
+                            inline_data: Some(GeminiInlineData {
+                                mime_type: image.media_type,
+                                data: image.data,
+                            }),
+                            ..Default::default()
+                        },
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        let request_body = GeminiRequest {
+            system_instruction: Some(GeminiContent {
+                role: None,
+                parts: vec![GeminiPart {
+                    text: request.system.text(),
+                    ..Default::default()
+                }],
+            }),
+            contents,
+            generation_config: GeminiGenerationConfig {
+                max_output_tokens: params.max_tokens,
+                temperature: params.temperature,
+                top_p: params.top_p,
+                thinking_config: params.reasoning_effort.map(|effort| GeminiThinkingConfig {
+                    thinking_budget: effort.budget_tokens(),
+                }),
+                response_mime_type: request
+                    .response_schema
+                    .as_ref()
+                    .map(|_| "application/json".to_string()),
+                response_json_schema: request.response_schema.map(|s| s.schema),
+            },
+        };
+
+        let url = format!("{}/{}:generateContent", Self::API_URL, self.config.model);
+
+        let mut builder = self
+            .http_client
+            .post(url)
+            .header("x-goog-api-key", &self.config.api_key)
+            .header("Content-Type", "application/json")
+            .json(&request_body);
+        if let Some(timeout) = params.timeout {
+            builder = builder.timeout(Duration::from_secs(timeout));
+        }
+
+        let response = builder
+            .send()
+            .await
+            .map_err(|e| LLMError::from_reqwest(Self::PROVIDER, e))?;
+
+        // Check response status
+        let status = response.status();
+
+        if !status.is_success() {
+            let retry_after = retry_after(response.headers());
+            let error_text = response
+                .text()
+                .await
+                .unwrap_or_else(|_| "Unknown error".to_string());
+
+            // Try to parse Gemini error format
+            let (error_type, message) = match serde_json::from_str::<GeminiError>(&error_text) {
+                Ok(gemini_error) => (gemini_error.error.status, gemini_error.error.message),
+                Err(_) => (None, error_text),
+            };
+
+            return Err(LLMError::from_status(
+                Self::PROVIDER,
+                status,
+                error_type.as_deref(),
+                message,
+                retry_after,
+            )
+            .into());
+        }
+
+        // Parse response body
+        let gemini_response: GeminiResponse = response
+            .json()
+            .await
+            .map_err(|e| LLMError::from_reqwest(Self::PROVIDER, e))?;
+
+        // Join the text parts of the first candidate
+        let text = gemini_response
+            .candidates
+            .into_iter()
+            .next()
+            .and_then(|candidate| candidate.content)
+            .map(|content| {
+                content
+                    .parts
+                    .into_iter()
+                    .map(|part| part.text)
+                    .collect::<String>()
+            })
+            .unwrap_or_default();
+
+        if text.is_empty() {
+            return Err(LLMError::BadResponse {
+                provider: Self::PROVIDER.to_string(),
+                message: "no content in response".to_string(),
+            }
+            .into());
+        }
+
+        Ok(LLMResponse {
+            text,
+            reasoning: None,
+            citations: Vec::new(),
+            usage: gemini_response.usage_metadata.map(Usage::from),
+        })
+    }
+}
@@ -0,0 +1,9 @@
+//! Google Gemini LLM integration module.
+
+pub mod client;
+pub mod payloads;
+pub mod secret;
+
+pub use client::GeminiClient;
+pub use payloads::GeminiConfig;
+pub use secret::GeminiSecret;
@@ -0,0 +1,30 @@
+use crate::system::ProviderSecret;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+/// Gemini Secret information
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GeminiSecret {
+    pub api_key: String,
+}
+
+impl ProviderSecret for GeminiSecret {
+    fn validate(&self) -> Result<()> {
+        if self.api_key.is_empty() {
+            anyhow::bail!("API key cannot be empty");
+        }
+        Ok(())
+    }
+
+    fn masked(&self) -> String {
+        if self.api_key.len() > 10 {
+            format!(
+                "{}...{}",
+                &self.api_key[..6],
+                &self.api_key[self.api_key.len() - 4..]
+            )
+        } else {
+            "***".to_string()
+        }
+    }
+}
@@ -0,0 +1,52 @@
+use crate::system::NetworkConfig;
+use anyhow::{Context, Result};
+use reqwest::{Certificate, ClientBuilder, Identity, NoProxy, Proxy};
+use std::fs;
+use std::time::Duration;
+
+/// HTTP client builder with the proxy, certificates and connect timeout from `[network]`.
+/// Every provider client starts from it.
+pub(crate) fn client_builder(network: &NetworkConfig) -> Result<ClientBuilder> {
+    let mut builder = reqwest::Client::builder();
+
+    if let Some(ref url) = network.proxy {
+        let proxy = Proxy::all(url)
+            .with_context(|| format!("Invalid proxy URL: {}", url))?
+            .no_proxy(network.no_proxy.as_deref().and_then(NoProxy::from_string));
+        builder = builder.proxy(proxy);
+    }
+
+    for path in &network.ca_certs {
+        let pem = fs::read(path)
+            .with_context(|| format!("Failed to read CA certificate: {}", path.display()))?;
+        let certs = Certificate::from_pem_bundle(&pem)
+            .with_context(|| format!("Invalid PEM CA certificate: {}", path.display()))?;
+        for cert in certs {
+            builder = builder.add_root_certificate(cert);
+        }
+    }
+
+    match (&network.client_cert, &network.client_key) {
+        (Some(cert_path), Some(key_path)) => {
+            let cert = fs::read(cert_path).with_context(|| {
+                format!("Failed to read client certificate: {}", cert_path.display())
+            })?;
+            let key = fs::read(key_path)
+                .with_context(|| format!("Failed to read client key: {}", key_path.display()))?;
+            let identity = Identity::from_pkcs8_pem(&cert, &key).context(
+                "Invalid client identity (expected a PEM certificate and a PKCS#8 PEM private key)",
+            )?;
+            builder = builder.identity(identity);
+        }
+        (None, None) => {}
+        _ => anyhow::bail!(
+            "Client authentication needs both network.client_cert and network.client_key"
+        ),
+    }
+
+    if let Some(secs) = network.connect_timeout {
+        builder = builder.connect_timeout(Duration::from_secs(secs));
+    }
+
+    Ok(builder)
+}
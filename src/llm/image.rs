@@ -0,0 +1,135 @@
+use crate::llm::{ImageData, Provider};
+use crate::system::LLMConfig;
+use anyhow::{Context, Result};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use std::fs;
+use std::path::Path;
+
+const MB: usize = 1024 * 1024;
+
+/// Ollama model families that take images
+const OLLAMA_VISION_MODELS: &[&str] = &[
+    "llava",
+    "bakllava",
+    "llama3.2-vision",
+    "llama4",
+    "gemma3",
+    "qwen2.5vl",
+    "minicpm-v",
+    "moondream",
+    "granite3.2-vision",
+    "mistral-small3.1",
+    "mistral-small3.2",
+];
+
+/// Image size and formats accepted by a provider's API
+struct ImageLimits {
+    /// Largest image in bytes, if the API sets a limit
+    max_bytes: Option<usize>,
+    media_types: &'static [&'static str],
+}
+
+impl ImageLimits {
+    fn of(provider: &Provider) -> Self {
+        match provider {
+            Provider::Anthropic => Self {
+                max_bytes: Some(5 * MB),
+                media_types: &["image/png", "image/jpeg", "image/gif", "image/webp"],
+            },
+            // Inline data counts towards the 20 MB request limit after base64 encoding
+            Provider::Gemini => Self {
+                max_bytes: Some(15 * MB),
+                media_types: &["image/png", "image/jpeg", "image/webp"],
+            },
+            Provider::Ollama => Self {
+                max_bytes: None,
+                media_types: &["image/png", "image/jpeg"],
+            },
+            // OpenAI, Azure OpenAI and OpenAI-compatible providers
+            _ => Self {
+                max_bytes: Some(20 * MB),
+                media_types: &["image/png", "image/jpeg", "image/gif", "image/webp"],
+            },
+        }
+    }
+}
+
+/// Check that the configured model takes images, before any are read or sent.
+/// Custom providers opt in with `vision = true`; Ollama models are matched by family name.
+pub fn check_vision(llm: &LLMConfig) -> Result<()> {
+    let supported = match &llm.provider {
+        Provider::Custom(name) => llm.providers.get(name).is_some_and(|p| p.vision),
+        Provider::Ollama => {
+            let name = llm.model.rsplit('/').next().unwrap_or(&llm.model);
+            let family = name.split(':').next().unwrap_or(name);
+            OLLAMA_VISION_MODELS.contains(&family)
+        }
+        _ => true,
+    };
+    if !supported {
+        anyhow::bail!(
+            "{} model {} is not known to accept images{}",
+            llm.provider.as_display(),
+            llm.model,
+            match llm.provider {
+                Provider::Custom(_) => ". Set `vision = true` for the provider if it does",
+                _ => ". Switch to a vision model such as llava or gemma3",
+            }
+        );
+    }
+    Ok(())
+}
+
+/// Load an image file to attach to a message, checking it against the provider's limits
+pub fn load_image(path: &Path, provider: &Provider) -> Result<ImageData> {
+    let bytes =
+        fs::read(path).with_context(|| format!("Failed to read image: {}", path.display()))?;
+    let media_type = detect_media_type(&bytes).with_context(|| {
+        format!(
+            "Unsupported image format: {}. Use PNG, JPEG, GIF or WebP",
+            path.display()
+        )
+    })?;
+
+    let limits = ImageLimits::of(provider);
+    if !limits.media_types.contains(&media_type) {
+        anyhow::bail!(
+            "{} does not accept {} images: {}",
+            provider.as_display(),
+            media_type,
+            path.display()
+        );
+    }
+    if let Some(max_bytes) = limits.max_bytes
+        && bytes.len() > max_bytes
+    {
+        anyhow::bail!(
+            "Image too large for {}: {} is {:.1} MB (limit {} MB)",
+            provider.as_display(),
+            path.display(),
+            bytes.len() as f64 / MB as f64,
+            max_bytes / MB
+        );
+    }
+
+    Ok(ImageData {
+        media_type: media_type.to_string(),
+        data: STANDARD.encode(&bytes),
+    })
+}
+
+/// MIME type from the file signature
+fn detect_media_type(bytes: &[u8]) -> Option<&'static str> {
+    if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some("image/png")
+    } else if bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some("image/jpeg")
+    } else if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+        Some("image/gif")
+    } else if bytes.len() >= 12 && bytes.starts_with(b"RIFF") && &bytes[8..12] == b"WEBP" {
+        Some("image/webp")
+    } else {
+        None
+    }
+}
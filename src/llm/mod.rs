@@ -1,19 +1,44 @@
 // LLM 통합 모듈
 
 pub mod anthropic;
+pub mod azure;
+pub mod budget;
 pub mod client;
+pub mod custom;
+pub mod discovery;
+pub mod error;
+pub mod gemini;
+pub(crate) mod http;
+pub mod image;
+pub mod ollama;
 pub mod openai;
 pub mod parser;
 pub mod presets;
 pub mod prompt;
 pub mod provider;
+pub mod replay;
+pub mod retry;
+pub(crate) mod stream;
 
 pub use anthropic::{AnthropicClient, AnthropicConfig, AnthropicSecret};
-pub use client::{Client, LLMClient, LLMMessage, LLMRole, create_client, verify_client};
-pub use openai::{OpenAIClient, OpenAIConfig, OpenAISecret};
+pub use azure::AzureOpenAISecret;
+pub use client::{
+    Backend, Citation, Client, ContentPart, GenerationParams, ImageData, LLMClient, LLMMessage,
+    LLMMode, LLMResponse, LLMRole, PromptSegment, ReasoningEffort, ResponseSchema, StreamDelta,
+    SystemPrompt, Usage, create_client, verify_client,
+};
+pub use custom::{CustomProviderConfig, CustomSecret};
+pub use error::LLMError;
+pub use gemini::{GeminiClient, GeminiConfig, GeminiSecret};
+pub use image::{check_vision, load_image};
+pub use ollama::{OllamaClient, OllamaConfig};
+pub use openai::{OpenAIApi, OpenAIAuth, OpenAIClient, OpenAIConfig, OpenAISecret};
 pub use parser::{
-    AskResponse, AutoResponse, CommandSuggestion, SuggestResponse, parse_auto_mode, parse_suggest,
+    AskResponse, AutoResponse, CommandPart, CommandSuggestion, ExplainResponse, PartKind,
+    PipelineStage, SuggestResponse, parse_auto_mode, parse_explain, parse_suggest,
 };
-pub use presets::load_presets;
+pub use presets::{ModelPricing, find_context_window, find_pricing, load_presets};
 pub use prompt::PromptBuilder;
 pub use provider::Provider;
+pub use replay::{ReplayClient, ReplayConfig, ReplayMode};
+pub use retry::RetryNotice;
@@ -0,0 +1,208 @@
+use crate::llm::client::{LLMClient, LLMRequest, LLMResponse, Usage};
+use crate::llm::error::LLMError;
+use crate::llm::http::client_builder;
+use crate::llm::ollama::payloads::{
+    OllamaConfig, OllamaError, OllamaMessage, OllamaOptions, OllamaRequest, OllamaResponse,
+    OllamaTags,
+};
+use crate::llm::presets::ModelInfo;
+use crate::llm::retry::retry_after;
+use crate::system::NetworkConfig;
+use anyhow::{Context, Result};
+use reqwest::Client;
+use std::time::Duration;
+
+/// Ollama LLM Client
+pub struct OllamaClient {
+    http_client: Client,
+    config: OllamaConfig,
+}
+
+impl OllamaClient {
+    /// Provider name used in errors
+    const PROVIDER: &'static str = "Ollama";
+
+    /// Default Ollama server URL
+    pub const DEFAULT_BASE_URL: &'static str = "http://localhost:11434";
+
+    /// Create a new OllamaClient
+    pub fn new(config: OllamaConfig, timeout: u64, network: &NetworkConfig) -> Result<Self> {
+        if config.base_url.is_empty() {
+            anyhow::bail!(
+                "Ollama server URL is not set. Please set it with `doum config set llm.ollama_url <url>`."
+            );
+        }
+
+        let http_client = client_builder(network)?
+            .timeout(Duration::from_secs(timeout))
+            .build()
+            .context("Failed to build HTTP client")?;
+
+        Ok(Self {
+            http_client,
+            config,
+        })
+    }
+
+    /// Build endpoint URL from the configured server URL
+    fn endpoint(&self, path: &str) -> String {
+        format!("{}{}", self.config.base_url.trim_end_matches('/'), path)
+    }
+
+    /// List models installed on the running Ollama server
+    pub async fn list_models(&self) -> Result<Vec<ModelInfo>> {
+        let response = self
+            .http_client
+            .get(self.endpoint("/api/tags"))
+            .send()
+            .await
+            .map_err(|e| {
+                if e.is_connect() {
+                    anyhow::anyhow!(
+                        "Failed to connect to Ollama server at {}. Is `ollama serve` running?",
+                        self.config.base_url
+                    )
+                } else {
+                    anyhow::anyhow!("Failed to send request to Ollama server: {}", e)
+                }
+            })?;
+
+        let status = response.status();
+        if !status.is_success() {
+            anyhow::bail!("Ollama API Error: {}", status);
+        }
+
+        let tags: OllamaTags = response
+            .json()
+            .await
+            .context("Failed to parse Ollama model list")?;
+
+        let models = tags
+            .models
+            .into_iter()
+            .map(|m| {
+                let description = m
+                    .details
+                    .map(|d| {
+                        [d.family, d.parameter_size]
+                            .into_iter()
+                            .flatten()
+                            .collect::<Vec<_>>()
+                            .join(" ")
+                    })
+                    .unwrap_or_default();
+
+                ModelInfo {
+                    id: m.name.clone(),
+                    name: m.name,
+                    description,
+                    context_window: None,
+                    pricing: None,
+                }
+            })
+            .collect();
+
+        Ok(models)
+    }
+}
+
+#[async_trait::async_trait]
+impl LLMClient for OllamaClient {
+    async fn generate(&self, request: LLMRequest) -> Result<LLMResponse> {
+        let params = request.params;
+
+        // Ollama takes the system prompt as the first chat message
+        let mut messages = vec![OllamaMessage {
+            role: "system".to_string(),
+            content: request.system.text(),
+            images: Vec::new(),
+        }];
+        messages.extend(request.messages.into_iter().map(|m| OllamaMessage {
+            role: m.role.as_str().to_string(),
+            content: m.text(),
+            images: m.images().map(|image| image.data.clone()).collect(),
+        }));
+
+        let request_body = OllamaRequest {
+            model: self.config.model.clone(),
+            messages,
+            stream: false,
+            format: request.response_schema.map(|s| s.schema),
+            options: OllamaOptions {
+                num_predict: params.max_tokens,
+                temperature: params.temperature,
+                top_p: params.top_p,
+            },
+        };
+
+        let mut builder = self
+            .http_client
+            .post(self.endpoint("/api/chat"))
+            .header("Content-Type", "application/json")
+            .json(&request_body);
+        if let Some(timeout) = params.timeout {
+            builder = builder.timeout(Duration::from_secs(timeout));
+        }
+
+        let response = builder.send().await.map_err(|e| {
+            if e.is_connect() {
+                LLMError::Network {
+                    provider: Self::PROVIDER.to_string(),
+                    message: format!(
+                        "no server at {}. Is `ollama serve` running?",
+                        self.config.base_url
+                    ),
+                }
+            } else {
+                LLMError::from_reqwest(Self::PROVIDER, e)
+            }
+        })?;
+
+        // Check response status
+        let status = response.status();
+
+        if !status.is_success() {
+            let retry_after = retry_after(response.headers());
+            let error_text = response
+                .text()
+                .await
+                .unwrap_or_else(|_| "Unknown error".to_string());
+
+            // Try to parse Ollama error format
+            let message = match serde_json::from_str::<OllamaError>(&error_text) {
+                Ok(ollama_error) => ollama_error.error,
+                Err(_) => error_text,
+            };
+
+            return Err(
+                LLMError::from_status(Self::PROVIDER, status, None, message, retry_after).into(),
+            );
+        }
+
+        // Parse response body
+        let ollama_response: OllamaResponse = response
+            .json()
+            .await
+            .map_err(|e| LLMError::from_reqwest(Self::PROVIDER, e))?;
+
+        if ollama_response.message.content.is_empty() {
+            return Err(LLMError::BadResponse {
+                provider: Self::PROVIDER.to_string(),
+                message: "no content in response".to_string(),
+            }
+            .into());
+        }
+
+        Ok(LLMResponse {
+            text: ollama_response.message.content,
+            reasoning: None,
+            citations: Vec::new(),
+            usage: Some(Usage {
+                input_tokens: ollama_response.prompt_eval_count,
+                output_tokens: ollama_response.eval_count,
+                cached_tokens: 0,
+                cache_write_tokens: 0,
+            }),
+        })
+    }
+}
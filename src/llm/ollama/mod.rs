@@ -0,0 +1,7 @@
+//! Ollama (local models) LLM integration module.
+
+pub mod client;
+pub mod payloads;
+
+pub use client::OllamaClient;
+pub use payloads::OllamaConfig;
@@ -0,0 +1,75 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OllamaConfig {
+    pub model: String,
+    pub base_url: String,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct OllamaRequest {
+    pub model: String,
+    pub messages: Vec<OllamaMessage>,
+    pub stream: bool,
+    /// JSON schema constraining the output (structured outputs)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<serde_json::Value>,
+    pub options: OllamaOptions,
+}
+
+/// Model parameters overriding the Modelfile
+#[derive(Debug, Serialize)]
+pub(crate) struct OllamaOptions {
+    /// Output token limit
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub num_predict: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct OllamaMessage {
+    pub role: String,
+    pub content: String,
+    /// Base64-encoded images for vision models
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub images: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct OllamaResponse {
+    pub message: OllamaMessage,
+    /// Input tokens evaluated
+    #[serde(default)]
+    pub prompt_eval_count: u64,
+    /// Output tokens generated
+    #[serde(default)]
+    pub eval_count: u64,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct OllamaTags {
+    pub models: Vec<OllamaModel>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct OllamaModel {
+    pub name: String,
+    #[serde(default)]
+    pub details: Option<OllamaModelDetails>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct OllamaModelDetails {
+    #[serde(default)]
+    pub family: Option<String>,
+    #[serde(default)]
+    pub parameter_size: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct OllamaError {
+    pub error: String,
+}
@@ -1,61 +1,79 @@
-use crate::llm::client::{LLMClient, LLMRequest};
+use crate::llm::client::{
+    Citation, GenerationParams, LLMClient, LLMRequest, LLMResponse, ReasoningEffort,
+    ResponseSchema, StreamDelta, Usage,
+};
+use crate::llm::error::LLMError;
+use crate::llm::http::client_builder;
 use crate::llm::openai::payloads::{
-    OpenAIConfig, OpenAIError, OpenAIOutput, OpenAIRequest, OpenAIResponse, OpenAIWebSearchTool,
+    OpenAIAnnotation, OpenAIApi, OpenAIAuth, OpenAIChatContent, OpenAIChatInputMessage,
+    OpenAIChatRequest, OpenAIChatResponse, OpenAIConfig, OpenAIError, OpenAIJsonSchema,
+    OpenAIModelList, OpenAIOutput, OpenAIReasoning, OpenAIRequest, OpenAIResponse,
+    OpenAIResponseFormat, OpenAIStreamEvent, OpenAITextConfig, OpenAIWebSearchTool,
 };
+use crate::llm::presets::ModelInfo;
+use crate::llm::retry::retry_after;
+use crate::llm::stream::read_events;
+use crate::system::NetworkConfig;
 use anyhow::{Context, Result};
-use reqwest::Client;
+use reqwest::{Client, RequestBuilder, Response};
+use serde::Serialize;
+use std::cmp::Reverse;
 use std::time::Duration;
 
 /// OpenAI LLM Client
 pub struct OpenAIClient {
     http_client: Client,
     config: OpenAIConfig,
+    timeout: Duration,
 }
 
 impl OpenAIClient {
-    /// OpenAI API URL
-    const API_URL: &'static str = "https://api.openai.com/v1/responses";
+    /// OpenAI API base URL
+    pub const DEFAULT_BASE_URL: &'static str = "https://api.openai.com/v1";
 
     /// Create a new OpenAIClient
-    pub fn new(config: OpenAIConfig, timeout: u64) -> Result<Self> {
-        if config.api_key.is_empty() {
-            anyhow::bail!(
-                "OpenAI API key is not set. Please configure it in the interactive config menu (doum config)."
-            );
+    pub fn new(config: OpenAIConfig, timeout: u64, network: &NetworkConfig) -> Result<Self> {
+        if config.auth != OpenAIAuth::None && config.api_key.is_empty() {
+            return Err(LLMError::Auth {
+                provider: config.name,
+                message: "API key is not set".to_string(),
+            }
+            .into());
         }
 
-        let http_client = Client::builder()
-            .timeout(Duration::from_secs(timeout))
+        let http_client = client_builder(network)?
             .build()
             .context("Failed to build HTTP client")?;
 
         Ok(Self {
             http_client,
             config,
+            timeout: Duration::from_secs(timeout),
         })
     }
-}
 
-#[async_trait::async_trait]
-impl LLMClient for OpenAIClient {
-    async fn generate(&self, request: LLMRequest) -> Result<String> {
-        // create OpenAI request payload
-        let openai_request = OpenAIRequest {
-            model: self.config.model.clone(),
-            instructions: Some(request.system),
-            input: request.messages,
-            tools: vec![OpenAIWebSearchTool {
-                tool_type: "web_search".to_string(),
-            }]
-            .into(),
+    /// Build endpoint URL for the configured API flavor
+    fn endpoint(&self) -> String {
+        format!(
+            "{}{}",
+            self.config.base_url.trim_end_matches('/'),
+            self.config.api.path()
+        )
+    }
+
+    /// Add the authentication and optional headers
+    fn authorize(&self, mut builder: RequestBuilder) -> RequestBuilder {
+        builder = match self.config.auth {
+            OpenAIAuth::Bearer => {
+                builder.header("Authorization", format!("Bearer {}", self.config.api_key))
+            }
+            OpenAIAuth::ApiKey => builder.header("api-key", &self.config.api_key),
+            OpenAIAuth::None => builder,
         };
 
-        // build request with headers
-        let mut builder = self
-            .http_client
-            .post(Self::API_URL)
-            .header("Authorization", format!("Bearer {}", self.config.api_key))
-            .header("Content-Type", "application/json");
+        if let Some(ref version) = self.config.api_version {
+            builder = builder.query(&[("api-version", version)]);
+        }
 
         // Optional headers
         if let Some(ref org) = self.config.organization {
@@ -65,53 +83,403 @@ impl LLMClient for OpenAIClient {
             builder = builder.header("OpenAI-Project", proj);
         }
 
+        builder
+    }
+
+    /// Send a JSON body to the endpoint and check the response status.
+    /// Streaming requests are only bounded until the response headers arrive.
+    async fn send<T: Serialize>(
+        &self,
+        body: &T,
+        stream: bool,
+        timeout: Duration,
+    ) -> Result<Response> {
+        // build request with headers
+        let builder = self.authorize(
+            self.http_client
+                .post(self.endpoint())
+                .header("Content-Type", "application/json"),
+        );
+
         // send request
-        let response = builder.json(&openai_request).send().await.map_err(|e| {
+        let builder = builder.json(body);
+        let timeout_error = || LLMError::Timeout {
+            provider: self.config.name.clone(),
+            message: format!("no response within {}s", timeout.as_secs()),
+        };
+        let result = if stream {
+            tokio::time::timeout(timeout, builder.send())
+                .await
+                .map_err(|_| timeout_error())?
+        } else {
+            builder.timeout(timeout).send().await
+        };
+
+        let response = result.map_err(|e| {
             if e.is_timeout() {
-                anyhow::anyhow!("Request timeout")
-            } else if e.is_connect() {
-                anyhow::anyhow!("Failed to connect to OpenAI API")
+                timeout_error()
             } else {
-                anyhow::anyhow!("Failed to send request to OpenAI API: {}", e)
+                LLMError::from_reqwest(&self.config.name, e)
             }
         })?;
 
-        // check response status
+        self.check_status(response).await
+    }
+
+    /// Turn an error status into an `LLMError`
+    async fn check_status(&self, response: Response) -> Result<Response> {
         let status = response.status();
 
         if !status.is_success() {
+            let retry_after = retry_after(response.headers());
             let error_text = response
                 .text()
                 .await
                 .unwrap_or_else(|_| "Unknown error".to_string());
 
             // Try to parse OpenAI error format
-            if let Ok(openai_error) = serde_json::from_str::<OpenAIError>(&error_text) {
-                anyhow::bail!(
-                    "OpenAI API Error ({}): {}",
-                    status,
-                    openai_error.error.message
+            let (error_type, message) = match serde_json::from_str::<OpenAIError>(&error_text) {
+                Ok(openai_error) => (
+                    openai_error.error.kind().map(str::to_string),
+                    openai_error.error.message,
+                ),
+                Err(_) => (None, error_text),
+            };
+
+            return Err(LLMError::from_status(
+                &self.config.name,
+                status,
+                error_type.as_deref(),
+                message,
+                retry_after,
+            )
+            .into());
+        }
+
+        Ok(response)
+    }
+
+    /// List the models available to the API key (`GET /models`), newest first
+    pub async fn list_models(&self) -> Result<Vec<ModelInfo>> {
+        let url = format!("{}/models", self.config.base_url.trim_end_matches('/'));
+        let response = self
+            .authorize(self.http_client.get(url))
+            .timeout(self.timeout)
+            .send()
+            .await
+            .map_err(|e| LLMError::from_reqwest(&self.config.name, e))?;
+        let response = self.check_status(response).await?;
+
+        let mut list: OpenAIModelList = response
+            .json()
+            .await
+            .map_err(|e| LLMError::from_reqwest(&self.config.name, e))?;
+        list.data.sort_by_key(|m| Reverse(m.created));
+
+        let models = list
+            .data
+            .into_iter()
+            .map(|m| ModelInfo {
+                id: m.id.clone(),
+                name: m.id,
+                description: String::new(),
+                context_window: None,
+                pricing: None,
+            })
+            .collect();
+
+        Ok(models)
+    }
+
+    /// Error for a response without any answer text
+    fn no_content(&self) -> LLMError {
+        LLMError::BadResponse {
+            provider: self.config.name.clone(),
+            message: "no content in response".to_string(),
+        }
+    }
+
+    /// Whether the model is an OpenAI reasoning model (o-series, GPT-5).
+    /// They reject `temperature` and `top_p`, and spend part of the output limit on reasoning.
+    fn is_reasoning_model(&self) -> bool {
+        let model = self.config.model.to_lowercase();
+        let model = model.rsplit('/').next().unwrap_or_default();
+        ["o1", "o3", "o4"]
+            .iter()
+            .any(|series| model == *series || model.starts_with(&format!("{}-", series)))
+            || (model.starts_with("gpt-5") && !model.starts_with("gpt-5-chat"))
+    }
+
+    /// `temperature` and `top_p` to send, left out for reasoning models
+    fn sampling(&self, params: &GenerationParams) -> (Option<f64>, Option<f64>) {
+        if self.is_reasoning_model() {
+            if params.temperature.is_some() || params.top_p.is_some() {
+                tracing::debug!(
+                    "Not sending temperature/top_p: {} is a reasoning model",
+                    self.config.model
                 );
             }
+            return (None, None);
+        }
+        (params.temperature, params.top_p)
+    }
+
+    /// Reasoning effort to send, left out for models without reasoning, which reject it
+    fn effort(&self, params: &GenerationParams) -> Option<ReasoningEffort> {
+        let effort = params.effort(self.config.use_thinking)?;
+        if !self.is_reasoning_model() {
+            tracing::debug!(
+                "Not sending reasoning effort: {} is not a reasoning model",
+                self.config.model
+            );
+            return None;
+        }
+        Some(effort)
+    }
+
+    /// Schema to send, none when the server does not support structured outputs
+    /// (the parser then extracts the JSON from the text)
+    fn response_schema(&self, schema: Option<ResponseSchema>) -> Option<ResponseSchema> {
+        schema.filter(|_| self.config.structured_outputs)
+    }
 
-            anyhow::bail!("OpenAI API Error: {} - {}", status, error_text);
+    /// Create a `/v1/responses` request payload
+    fn responses_request(&self, request: LLMRequest, stream: bool) -> OpenAIRequest {
+        let params = request.params;
+        let (temperature, top_p) = self.sampling(&params);
+        OpenAIRequest {
+            model: self.config.model.clone(),
+            instructions: Some(request.system.text()),
+            input: request.messages.into_iter().map(Into::into).collect(),
+            tools: self.config.use_web_search.then(|| {
+                vec![OpenAIWebSearchTool {
+                    tool_type: "web_search".to_string(),
+                }]
+            }),
+            reasoning: self.effort(&params).map(|effort| OpenAIReasoning {
+                effort: effort.as_str().to_string(),
+                summary: "auto".to_string(),
+            }),
+            max_output_tokens: params.max_tokens,
+            temperature,
+            top_p,
+            text: self
+                .response_schema(request.response_schema)
+                .map(|s| OpenAITextConfig {
+                    format: OpenAIJsonSchema {
+                        format_type: Some("json_schema".to_string()),
+                        name: s.name,
+                        schema: s.schema,
+                        strict: true,
+                    },
+                }),
+            stream,
         }
+    }
+
+    /// Generate using the `/v1/responses` API
+    async fn generate_responses(&self, request: LLMRequest) -> Result<LLMResponse> {
+        let timeout = request.params.timeout_or(self.timeout);
+        let openai_request = self.responses_request(request, false);
+
+        let response = self.send(&openai_request, false, timeout).await?;
 
         // Parse response body
         let openai_response: OpenAIResponse = response
             .json()
             .await
-            .context("Failed to parse OpenAI response")?;
+            .map_err(|e| LLMError::from_reqwest(&self.config.name, e))?;
 
-        // Extract message content
+        // Extract message content, cited sources and reasoning summary
+        let mut text = String::new();
+        let mut citations = Vec::new();
+        let mut summaries = Vec::new();
         for output in openai_response.output {
-            if let OpenAIOutput::Message { content } = output
-                && let Some(first_content) = content.first()
-            {
-                return Ok(first_content.text.clone());
+            match output {
+                OpenAIOutput::Message { content } => {
+                    for part in content {
+                        text.push_str(&part.text);
+                        for annotation in part.annotations {
+                            if let OpenAIAnnotation::UrlCitation { url, title } = annotation {
+                                Citation::push_unique(&mut citations, url, title);
+                            }
+                        }
+                    }
+                }
+                OpenAIOutput::Reasoning { summary } => {
+                    summaries.extend(summary.into_iter().map(|s| s.text));
+                }
+                _ => {}
+            }
+        }
+
+        if text.is_empty() {
+            return Err(match openai_response.incomplete_details {
+                Some(details) if details.reason == "max_output_tokens" => LLMError::BadResponse {
+                    provider: self.config.name.clone(),
+                    message: "output token limit reached before the answer (reasoning tokens count towards modes.<mode>.max_tokens)".to_string(),
+                },
+                _ => self.no_content(),
             }
+            .into());
         }
 
-        anyhow::bail!("No content in OpenAI response")
+        Ok(LLMResponse {
+            text,
+            reasoning: (!summaries.is_empty()).then(|| summaries.join("\n\n")),
+            citations,
+            usage: openai_response.usage.map(Usage::from),
+        })
+    }
+
+    /// Generate using the `/v1/chat/completions` API
+    async fn generate_chat(&self, request: LLMRequest) -> Result<LLMResponse> {
+        let params = request.params;
+        let (temperature, top_p) = self.sampling(&params);
+
+        // Chat Completions takes the system prompt as the first message
+        let mut messages = vec![OpenAIChatInputMessage {
+            role: "system".to_string(),
+            content: OpenAIChatContent::Text(request.system.text()),
+        }];
+        messages.extend(request.messages.into_iter().map(Into::into));
+
+        let chat_request = OpenAIChatRequest {
+            model: self.config.model.clone(),
+            messages,
+            reasoning_effort: self
+                .effort(&params)
+                .map(|effort| effort.as_str().to_string()),
+            max_completion_tokens: params.max_tokens,
+            temperature,
+            top_p,
+            response_format: self.response_schema(request.response_schema).map(|s| {
+                OpenAIResponseFormat {
+                    format_type: "json_schema".to_string(),
+                    json_schema: OpenAIJsonSchema {
+                        format_type: None,
+                        name: s.name,
+                        schema: s.schema,
+                        strict: true,
+                    },
+                }
+            }),
+        };
+
+        let response = self
+            .send(&chat_request, false, params.timeout_or(self.timeout))
+            .await?;
+
+        // Parse response body
+        let chat_response: OpenAIChatResponse = response
+            .json()
+            .await
+            .map_err(|e| LLMError::from_reqwest(&self.config.name, e))?;
+
+        let message = chat_response
+            .choices
+            .into_iter()
+            .next()
+            .map(|choice| choice.message)
+            .ok_or_else(|| self.no_content())?;
+
+        let text = message.content.ok_or_else(|| self.no_content())?;
+
+        Ok(LLMResponse {
+            text,
+            reasoning: message.reasoning_content.filter(|r| !r.is_empty()),
+            citations: Vec::new(),
+            usage: chat_response.usage.map(Usage::from),
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl LLMClient for OpenAIClient {
+    async fn generate(&self, request: LLMRequest) -> Result<LLMResponse> {
+        match self.config.api {
+            OpenAIApi::Responses => self.generate_responses(request).await,
+            OpenAIApi::ChatCompletions => self.generate_chat(request).await,
+        }
+    }
+
+    async fn generate_stream(
+        &self,
+        request: LLMRequest,
+        on_delta: &mut (dyn for<'d> FnMut(StreamDelta<'d>) + Send),
+    ) -> Result<LLMResponse> {
+        // Only the Responses API is streamed
+        if self.config.api != OpenAIApi::Responses {
+            let response = self.generate(request).await?;
+            if let Some(ref reasoning) = response.reasoning {
+                on_delta(StreamDelta::Reasoning(reasoning));
+            }
+            on_delta(StreamDelta::Text(&response.text));
+            return Ok(response);
+        }
+
+        let timeout = request.params.timeout_or(self.timeout);
+        let openai_request = self.responses_request(request, true);
+        let response = self.send(&openai_request, true, timeout).await?;
+
+        let mut text = String::new();
+        let mut reasoning = String::new();
+        let mut citations = Vec::new();
+        let mut usage = None;
+        read_events(response, &self.config.name, timeout, |event| {
+            let Ok(stream_event) = serde_json::from_str::<OpenAIStreamEvent>(&event.data) else {
+                return Ok(());
+            };
+
+            match stream_event {
+                OpenAIStreamEvent::OutputTextDelta { delta } => {
+                    on_delta(StreamDelta::Text(&delta));
+                    text.push_str(&delta);
+                }
+                OpenAIStreamEvent::OutputTextAnnotationAdded {
+                    annotation: OpenAIAnnotation::UrlCitation { url, title },
+                } => {
+                    Citation::push_unique(&mut citations, url, title);
+                }
+                OpenAIStreamEvent::ReasoningSummaryTextDelta { delta } => {
+                    on_delta(StreamDelta::Reasoning(&delta));
+                    reasoning.push_str(&delta);
+                }
+                OpenAIStreamEvent::Completed { response } => {
+                    usage = response.usage.map(Usage::from);
+                }
+                OpenAIStreamEvent::Failed { response } => {
+                    let (error_type, message) = match response.error {
+                        Some(e) => (e.kind().map(str::to_string), e.message),
+                        None => (None, "Unknown error".to_string()),
+                    };
+                    return Err(LLMError::from_event(
+                        &self.config.name,
+                        error_type.as_deref(),
+                        message,
+                    )
+                    .into());
+                }
+                OpenAIStreamEvent::Error { code, message } => {
+                    return Err(
+                        LLMError::from_event(&self.config.name, code.as_deref(), message).into(),
+                    );
+                }
+                _ => {}
+            }
+            Ok(())
+        })
+        .await?;
+
+        if text.is_empty() {
+            return Err(self.no_content().into());
+        }
+
+        Ok(LLMResponse {
+            text,
+            reasoning: (!reasoning.is_empty()).then_some(reasoning),
+            citations,
+            usage,
+        })
     }
 }
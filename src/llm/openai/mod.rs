@@ -5,5 +5,5 @@ pub mod payloads;
 pub mod secret;
 
 pub use client::OpenAIClient;
-pub use payloads::OpenAIConfig;
+pub use payloads::{OpenAIApi, OpenAIAuth, OpenAIConfig};
 pub use secret::OpenAISecret;
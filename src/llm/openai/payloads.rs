@@ -1,23 +1,148 @@
-use crate::llm::LLMMessage;
+use crate::llm::client::{ContentPart, LLMMessage, Usage};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OpenAIConfig {
+    /// Display name used in error messages
+    pub name: String,
     pub model: String,
     pub api_key: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub organization: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub project: Option<String>,
+    /// Base URL including the version segment (e.g., https://api.openai.com/v1)
+    pub base_url: String,
+    pub api: OpenAIApi,
+    pub auth: OpenAIAuth,
+    /// `api-version` query parameter (Azure OpenAI)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub api_version: Option<String>,
+    pub use_thinking: bool,
+    /// Send the `web_search` tool (Responses API only)
+    pub use_web_search: bool,
+    /// Send the response schema as a strict `json_schema` format
+    pub structured_outputs: bool,
+}
+
+/// Request flavor of an OpenAI-compatible API
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OpenAIApi {
+    /// `/v1/responses`
+    #[default]
+    Responses,
+    /// `/v1/chat/completions`
+    ChatCompletions,
+}
+
+impl OpenAIApi {
+    /// Endpoint path relative to the base URL
+    pub fn path(&self) -> &'static str {
+        match self {
+            OpenAIApi::Responses => "/responses",
+            OpenAIApi::ChatCompletions => "/chat/completions",
+        }
+    }
+}
+
+/// How the API key is sent to an OpenAI-compatible API
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum OpenAIAuth {
+    /// `Authorization: Bearer <key>`
+    #[default]
+    Bearer,
+    /// `api-key: <key>`
+    ApiKey,
+    /// No authentication header
+    None,
 }
 
 #[derive(Debug, Serialize)]
 pub(crate) struct OpenAIRequest {
     pub model: String,
     pub instructions: Option<String>,
-    pub input: Vec<LLMMessage>,
+    pub input: Vec<OpenAIInputMessage>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tools: Option<Vec<OpenAIWebSearchTool>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reasoning: Option<OpenAIReasoning>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_output_tokens: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<OpenAITextConfig>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub stream: bool,
+}
+
+/// Responses API input message; text-only content is sent as a plain string
+#[derive(Debug, Serialize)]
+pub(crate) struct OpenAIInputMessage {
+    pub role: String,
+    pub content: OpenAIInputContent,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub(crate) enum OpenAIInputContent {
+    Text(String),
+    Parts(Vec<OpenAIInputPart>),
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub(crate) enum OpenAIInputPart {
+    InputText { text: String },
+    InputImage { image_url: String },
+}
+
+impl From<LLMMessage> for OpenAIInputMessage {
+    fn from(message: LLMMessage) -> Self {
+        let role = message.role.as_str().to_string();
+        let content = if message.images().next().is_none() {
+            OpenAIInputContent::Text(message.text())
+        } else {
+            OpenAIInputContent::Parts(
+                message
+                    .content
+                    .into_iter()
+                    .map(|part| match part {
+                        ContentPart::Text { text } => OpenAIInputPart::InputText { text },
+                        ContentPart::Image(image) => OpenAIInputPart::InputImage {
+                            image_url: image.data_url(),
+                        },
+                    })
+                    .collect(),
+            )
+        };
+        Self { role, content }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct OpenAITextConfig {
+    pub format: OpenAIJsonSchema,
+}
+
+/// Strict `json_schema` format (structured outputs)
+#[derive(Debug, Serialize)]
+pub(crate) struct OpenAIJsonSchema {
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub format_type: Option<String>,
+    pub name: String,
+    pub schema: serde_json::Value,
+    pub strict: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct OpenAIReasoning {
+    pub effort: String,
+    pub summary: String,
 }
 
 #[derive(Debug, Serialize)]
@@ -29,6 +154,42 @@ pub(crate) struct OpenAIWebSearchTool {
 #[derive(Debug, Deserialize)]
 pub(crate) struct OpenAIResponse {
     pub output: Vec<OpenAIOutput>,
+    #[serde(default)]
+    pub usage: Option<OpenAIUsage>,
+    /// Why the response stopped early, e.g. `max_output_tokens`
+    #[serde(default)]
+    pub incomplete_details: Option<OpenAIIncompleteDetails>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct OpenAIIncompleteDetails {
+    pub reason: String,
+}
+
+/// Token usage of the Responses API
+#[derive(Debug, Deserialize)]
+pub(crate) struct OpenAIUsage {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    #[serde(default)]
+    pub input_tokens_details: Option<OpenAITokenDetails>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct OpenAITokenDetails {
+    #[serde(default)]
+    pub cached_tokens: u64,
+}
+
+impl From<OpenAIUsage> for Usage {
+    fn from(usage: OpenAIUsage) -> Self {
+        Usage {
+            input_tokens: usage.input_tokens,
+            output_tokens: usage.output_tokens,
+            cached_tokens: usage.input_tokens_details.map_or(0, |d| d.cached_tokens),
+            cache_write_tokens: 0,
+        }
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -36,8 +197,8 @@ pub(crate) struct OpenAIResponse {
 #[serde(rename_all = "snake_case")]
 pub(crate) enum OpenAIOutput {
     Reasoning {
-        #[allow(dead_code)]
-        summary: Vec<String>,
+        #[serde(default)]
+        summary: Vec<ReasoningSummary>,
     },
     Message {
         content: Vec<OutputContent>,
@@ -50,9 +211,195 @@ pub(crate) enum OpenAIOutput {
 
 #[derive(Debug, Deserialize)]
 pub(crate) struct OutputContent {
+    #[serde(default)]
+    pub text: String,
+    #[serde(default)]
+    pub annotations: Vec<OpenAIAnnotation>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type")]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum OpenAIAnnotation {
+    UrlCitation {
+        url: String,
+        title: Option<String>,
+    },
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct ReasoningSummary {
     pub text: String,
 }
 
+/// Server-sent event of the Responses API
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type")]
+pub(crate) enum OpenAIStreamEvent {
+    #[serde(rename = "response.output_text.delta")]
+    OutputTextDelta { delta: String },
+    #[serde(rename = "response.output_text.annotation.added")]
+    OutputTextAnnotationAdded { annotation: OpenAIAnnotation },
+    #[serde(rename = "response.reasoning_summary_text.delta")]
+    ReasoningSummaryTextDelta { delta: String },
+    #[serde(rename = "response.failed")]
+    Failed { response: OpenAIFailedResponse },
+    #[serde(rename = "response.completed")]
+    Completed { response: OpenAICompletedResponse },
+    #[serde(rename = "error")]
+    Error {
+        #[serde(default)]
+        code: Option<String>,
+        message: String,
+    },
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct OpenAICompletedResponse {
+    #[serde(default)]
+    pub usage: Option<OpenAIUsage>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct OpenAIFailedResponse {
+    pub error: Option<ErrorDetail>,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct OpenAIChatRequest {
+    pub model: String,
+    pub messages: Vec<OpenAIChatInputMessage>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reasoning_effort: Option<String>,
+    /// Output token limit (`max_tokens` is deprecated and rejected by reasoning models)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_completion_tokens: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response_format: Option<OpenAIResponseFormat>,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct OpenAIResponseFormat {
+    #[serde(rename = "type")]
+    pub format_type: String,
+    pub json_schema: OpenAIJsonSchema,
+}
+
+/// Chat Completions request message; text-only content is sent as a plain string
+#[derive(Debug, Serialize)]
+pub(crate) struct OpenAIChatInputMessage {
+    pub role: String,
+    pub content: OpenAIChatContent,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub(crate) enum OpenAIChatContent {
+    Text(String),
+    Parts(Vec<OpenAIChatPart>),
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub(crate) enum OpenAIChatPart {
+    Text { text: String },
+    ImageUrl { image_url: OpenAIImageUrl },
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct OpenAIImageUrl {
+    pub url: String,
+}
+
+impl From<LLMMessage> for OpenAIChatInputMessage {
+    fn from(message: LLMMessage) -> Self {
+        let role = message.role.as_str().to_string();
+        let content = if message.images().next().is_none() {
+            OpenAIChatContent::Text(message.text())
+        } else {
+            OpenAIChatContent::Parts(
+                message
+                    .content
+                    .into_iter()
+                    .map(|part| match part {
+                        ContentPart::Text { text } => OpenAIChatPart::Text { text },
+                        ContentPart::Image(image) => OpenAIChatPart::ImageUrl {
+                            image_url: OpenAIImageUrl {
+                                url: image.data_url(),
+                            },
+                        },
+                    })
+                    .collect(),
+            )
+        };
+        Self { role, content }
+    }
+}
+
+/// Chat Completions response message
+#[derive(Debug, Deserialize)]
+pub(crate) struct OpenAIChatMessage {
+    #[serde(default)]
+    pub content: Option<String>,
+    /// Reasoning text returned by some compatible servers (e.g., vLLM)
+    #[serde(default)]
+    pub reasoning_content: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct OpenAIChatResponse {
+    pub choices: Vec<OpenAIChatChoice>,
+    #[serde(default)]
+    pub usage: Option<OpenAIChatUsage>,
+}
+
+/// Token usage of the Chat Completions API
+#[derive(Debug, Deserialize)]
+pub(crate) struct OpenAIChatUsage {
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    #[serde(default)]
+    pub prompt_tokens_details: Option<OpenAITokenDetails>,
+}
+
+impl From<OpenAIChatUsage> for Usage {
+    fn from(usage: OpenAIChatUsage) -> Self {
+        Usage {
+            input_tokens: usage.prompt_tokens,
+            output_tokens: usage.completion_tokens,
+            cached_tokens: usage.prompt_tokens_details.map_or(0, |d| d.cached_tokens),
+            cache_write_tokens: 0,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct OpenAIChatChoice {
+    pub message: OpenAIChatMessage,
+}
+
+/// `GET /models` response
+#[derive(Debug, Deserialize)]
+pub(crate) struct OpenAIModelList {
+    pub data: Vec<OpenAIModel>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct OpenAIModel {
+    pub id: String,
+    /// Unix timestamp of the model release
+    #[serde(default)]
+    pub created: u64,
+}
+
 #[derive(Debug, Deserialize)]
 pub(crate) struct OpenAIError {
     pub error: ErrorDetail,
@@ -64,4 +411,17 @@ pub(crate) struct ErrorDetail {
     pub message: String,
     #[serde(rename = "type")]
     pub error_type: Option<String>,
+    /// Specific error code (e.g., `invalid_api_key`, `context_length_exceeded`)
+    #[serde(default)]
+    pub code: Option<serde_json::Value>,
+}
+
+impl ErrorDetail {
+    /// Most specific error kind: the code when it is a string, otherwise the type
+    pub fn kind(&self) -> Option<&str> {
+        self.code
+            .as_ref()
+            .and_then(|code| code.as_str())
+            .or(self.error_type.as_deref())
+    }
 }
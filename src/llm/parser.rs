@@ -1,8 +1,11 @@
 use anyhow::{Context, Result};
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 
 /// Select Mode Response
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[schemars(deny_unknown_fields)]
 pub struct AutoResponse {
     pub mode: String,
     pub reason: String,
@@ -12,30 +15,118 @@ pub struct AutoResponse {
 pub type AskResponse = String;
 
 /// Command Suggestion
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[schemars(deny_unknown_fields)]
 pub struct CommandSuggestion {
     pub cmd: String,
     pub description: String,
 }
 
 /// Suggest Mode Response
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[schemars(deny_unknown_fields)]
 pub struct SuggestResponse {
     pub suggestions: Vec<CommandSuggestion>,
 }
 
+impl SuggestResponse {
+    /// Suggestions as text, to keep them in a conversation history
+    pub fn to_message(&self) -> String {
+        let suggested: Vec<_> = self
+            .suggestions
+            .iter()
+            .map(|s| format!("- `{}`: {}", s.cmd, s.description))
+            .collect();
+        format!("Suggested commands:\n{}", suggested.join("\n"))
+    }
+}
+
+/// Kind of a command part
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+// Variant doc comments would turn the schema's flat `enum` into a `oneOf`, which
+// strict structured outputs reject; the kinds are described on `CommandPart::kind`
+pub enum PartKind {
+    // Option or switch
+    Flag,
+    // Positional argument, path or pattern
+    Argument,
+    // Redirection or here-document
+    Redirection,
+    // Environment variable assignment or expansion
+    Variable,
+    // Command substitution or subshell
+    Substitution,
+}
+
+impl PartKind {
+    /// Convert to string representation
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PartKind::Flag => "flag",
+            PartKind::Argument => "argument",
+            PartKind::Redirection => "redirection",
+            PartKind::Variable => "variable",
+            PartKind::Substitution => "substitution",
+        }
+    }
+}
+
+/// Annotated part of a pipeline stage
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct CommandPart {
+    /// flag: option or switch (`-r`, `--name=x`); argument: positional argument, path or pattern;
+    /// redirection: redirection or here-document (`> out.txt`, `2>&1`, `<<EOF`);
+    /// variable: environment variable assignment or expansion; substitution: command substitution or subshell
+    pub kind: PartKind,
+    /// Text as written in the command
+    pub text: String,
+    pub description: String,
+}
+
+/// One command of a pipeline or command list
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct PipelineStage {
+    /// Operator joining it to the previous stage (`|`, `&&`, `||`, `;`), empty for the first
+    pub operator: String,
+    /// Stage as written in the command
+    pub command: String,
+    pub description: String,
+    pub parts: Vec<CommandPart>,
+}
+
+/// Explain Mode Response
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct ExplainResponse {
+    /// What the whole command does
+    pub summary: String,
+    pub stages: Vec<PipelineStage>,
+    /// Changes to files, processes, the network or the system
+    pub side_effects: Vec<String>,
+}
+
 /// parse Auto Mode response
 pub fn parse_auto_mode(json_str: &str) -> Result<AutoResponse> {
-    let cleaned = extract_json(json_str);
-
-    serde_json::from_str(&cleaned).context("Failed to parse Auto Mode response")
+    parse_json(json_str).context("Failed to parse Auto Mode response")
 }
 
 /// parse Suggest response
 pub fn parse_suggest(json_str: &str) -> Result<SuggestResponse> {
-    let cleaned = extract_json(json_str);
+    parse_json(json_str).context("Failed to parse Suggest response")
+}
+
+/// parse Explain response
+pub fn parse_explain(json_str: &str) -> Result<ExplainResponse> {
+    parse_json(json_str).context("Failed to parse Explain response")
+}
 
-    serde_json::from_str(&cleaned).context("Failed to parse Suggest response")
+/// Parse a JSON response. Structured outputs return bare JSON; JSON embedded in
+/// free text is only extracted as a fallback for providers without that feature.
+fn parse_json<T: DeserializeOwned>(text: &str) -> serde_json::Result<T> {
+    serde_json::from_str(text).or_else(|_| serde_json::from_str(&extract_json(text)))
 }
 
 /// Extract JSON content from text (handles code blocks and surrounding text)
@@ -66,3 +157,40 @@ fn extract_json(text: &str) -> String {
     // Return original text if no JSON found
     text.to_string()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::ResponseSchema;
+
+    #[test]
+    fn response_schemas_have_no_one_of() {
+        // Strict structured outputs reject `oneOf`
+        let schemas = [
+            ResponseSchema::of::<SuggestResponse>("suggest_response"),
+            ResponseSchema::of::<AutoResponse>("auto_response"),
+            ResponseSchema::of::<ExplainResponse>("explain_response"),
+        ];
+        for schema in schemas {
+            let json = schema.schema.to_string();
+            assert!(!json.contains("oneOf"), "{}: {}", schema.name, json);
+        }
+    }
+
+    #[test]
+    fn part_kind_schema_is_a_flat_enum() {
+        let schema = ResponseSchema::of::<ExplainResponse>("explain_response").schema;
+        let kind = &schema["properties"]["stages"]["items"]["properties"]["parts"]["items"]["properties"]
+            ["kind"];
+        assert_eq!(
+            kind["enum"],
+            serde_json::json!([
+                "flag",
+                "argument",
+                "redirection",
+                "variable",
+                "substitution"
+            ])
+        );
+    }
+}
@@ -1,16 +1,49 @@
-use crate::llm::Provider;
+use crate::llm::{Provider, Usage};
 use rust_embed::RustEmbed;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 #[derive(RustEmbed)]
 #[folder = "static/presets/"]
 struct PresetModels;
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ModelInfo {
     pub id: String,
     pub name: String,
     pub description: String,
+    /// Input and output tokens the model can attend to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context_window: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pricing: Option<ModelPricing>,
+}
+
+/// Model prices in USD per million tokens
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+pub struct ModelPricing {
+    pub input: f64,
+    /// Price of input tokens read from the prompt cache (defaults to `input`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cached_input: Option<f64>,
+    /// Price of input tokens written to the prompt cache (defaults to `input`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_write: Option<f64>,
+    pub output: f64,
+}
+
+impl ModelPricing {
+    /// Estimated cost in USD of the given usage
+    pub fn cost(&self, usage: &Usage) -> f64 {
+        let cached = usage.cached_tokens.min(usage.input_tokens);
+        let written = usage.cache_write_tokens.min(usage.input_tokens - cached);
+        let uncached = usage.input_tokens - cached - written;
+
+        (uncached as f64 * self.input
+            + cached as f64 * self.cached_input.unwrap_or(self.input)
+            + written as f64 * self.cache_write.unwrap_or(self.input)
+            + usage.output_tokens as f64 * self.output)
+            / 1_000_000.0
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -31,3 +64,19 @@ pub fn load_presets(provider: &Provider) -> Vec<ModelInfo> {
 
     vec![]
 }
+
+/// Look up the preset context window of a model
+pub fn find_context_window(provider: &Provider, model: &str) -> Option<u64> {
+    load_presets(provider)
+        .into_iter()
+        .find(|m| m.id == model)
+        .and_then(|m| m.context_window)
+}
+
+/// Look up the preset prices of a model
+pub fn find_pricing(provider: &Provider, model: &str) -> Option<ModelPricing> {
+    load_presets(provider)
+        .into_iter()
+        .find(|m| m.id == model)
+        .and_then(|m| m.pricing)
+}
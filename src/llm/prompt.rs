@@ -1,3 +1,4 @@
+use crate::llm::SystemPrompt;
 use crate::system::SystemInfo;
 use handlebars::Handlebars;
 use rust_embed::RustEmbed;
@@ -56,23 +57,23 @@ impl PromptBuilder {
     }
 
     /// Create Mode Select message array
-    pub fn build_auto_mode(&self) -> String {
+    pub fn build_auto_mode(&self) -> SystemPrompt {
         let common_prompt = self.build_common_prompt();
         let mode_select_template = Self::load_prompt("mode_select.md");
 
-        Self::concat_prompts(vec![&common_prompt, &mode_select_template])
+        Self::concat_prompts(vec![common_prompt, mode_select_template])
     }
 
     /// Create Ask mode message array
-    pub fn build_ask(&self) -> String {
+    pub fn build_ask(&self) -> SystemPrompt {
         let common_prompt = self.build_common_prompt();
         let ask_prompt = Self::load_prompt("ask.md");
 
-        Self::concat_prompts(vec![&common_prompt, &ask_prompt])
+        Self::concat_prompts(vec![common_prompt, ask_prompt])
     }
 
     /// Create Suggest mode message array
-    pub fn build_suggest(&self) -> String {
+    pub fn build_suggest(&self) -> SystemPrompt {
         let common_prompt = self.build_common_prompt();
         let suggest_template = Self::load_prompt("suggest.md");
 
@@ -86,11 +87,50 @@ impl PromptBuilder {
             .render_template(&suggest_template, &data)
             .unwrap_or(suggest_template);
 
-        Self::concat_prompts(vec![&common_prompt, &suggest_prompt])
+        Self::concat_prompts(vec![common_prompt, suggest_prompt])
     }
 
-    /// Concatenate multiple prompt sections
-    fn concat_prompts(prompts: Vec<&str>) -> String {
-        prompts.join("\n\n---\n\n")
+    /// Create Explain mode message array
+    pub fn build_explain(&self) -> SystemPrompt {
+        let common_prompt = self.build_common_prompt();
+        let explain_template = Self::load_prompt("explain.md");
+
+        let data = json!({
+            "os": self.system_info.os.as_str(),
+            "shell": self.system_info.shell.as_str(),
+        });
+
+        let explain_prompt = self
+            .handlebars
+            .render_template(&explain_template, &data)
+            .unwrap_or(explain_template);
+
+        Self::concat_prompts(vec![common_prompt, explain_prompt])
+    }
+
+    /// Create Fix mode message array
+    pub fn build_fix(&self) -> SystemPrompt {
+        let common_prompt = self.build_common_prompt();
+        let fix_template = Self::load_prompt("fix.md");
+
+        let data = json!({
+            "os": self.system_info.os.as_str(),
+            "shell": self.system_info.shell.as_str(),
+        });
+
+        let fix_prompt = self
+            .handlebars
+            .render_template(&fix_template, &data)
+            .unwrap_or(fix_template);
+
+        Self::concat_prompts(vec![common_prompt, fix_prompt])
+    }
+
+    /// Concatenate multiple prompt sections.
+    /// They only depend on the system and the working directory, so each may be cached.
+    fn concat_prompts(prompts: Vec<String>) -> SystemPrompt {
+        prompts
+            .into_iter()
+            .fold(SystemPrompt::default(), SystemPrompt::cacheable)
     }
 }
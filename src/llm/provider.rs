@@ -1,22 +1,31 @@
+use crate::system::LLMConfig;
 use anyhow::Result;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::fmt;
 use std::str::FromStr;
 
 /// LLM Provider enumeration
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
-#[serde(rename_all = "lowercase")]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Provider {
     OpenAI,
     Anthropic,
+    AzureOpenAI,
+    Gemini,
+    Ollama,
+    /// User-defined OpenAI-compatible provider declared in `[llm.providers.<name>]`
+    Custom(String),
 }
 
 impl Provider {
     /// Convert to string representation
-    pub fn as_str(&self) -> &'static str {
+    pub fn as_str(&self) -> &str {
         match self {
             Provider::OpenAI => "openai",
             Provider::Anthropic => "anthropic",
+            Provider::AzureOpenAI => "azure",
+            Provider::Gemini => "gemini",
+            Provider::Ollama => "ollama",
+            Provider::Custom(name) => name,
         }
     }
 
@@ -25,15 +34,67 @@ impl Provider {
         match self {
             Provider::OpenAI => "OpenAI".to_string(),
             Provider::Anthropic => "Anthropic".to_string(),
+            Provider::AzureOpenAI => "Azure OpenAI".to_string(),
+            Provider::Gemini => "Google Gemini".to_string(),
+            Provider::Ollama => "Ollama".to_string(),
+            Provider::Custom(name) => name.clone(),
         }
     }
 
-    /// Get all available providers
+    /// Whether the provider needs a secret stored in the keyring
+    pub fn requires_secret(&self) -> bool {
+        !matches!(self, Provider::Ollama)
+    }
+
+    /// Get all built-in providers
     pub fn all() -> Vec<Provider> {
-        vec![Provider::OpenAI, Provider::Anthropic]
+        vec![
+            Provider::OpenAI,
+            Provider::Anthropic,
+            Provider::AzureOpenAI,
+            Provider::Gemini,
+            Provider::Ollama,
+        ]
+    }
+
+    /// Get built-in providers followed by the custom providers declared in config
+    pub fn available(config: &LLMConfig) -> Vec<Provider> {
+        let mut providers = Self::all();
+        providers.extend(
+            config
+                .providers
+                .keys()
+                .map(|name| name.parse())
+                .filter_map(Result::ok)
+                .filter(|p| matches!(p, Provider::Custom(_))),
+        );
+        providers
     }
 
-    /// Get all provider names as strings
+    /// Parse a provider name, accepting only the providers available in `config`,
+    /// so a misspelled built-in name is not taken for an undeclared custom provider
+    pub fn parse_available(s: &str, config: &LLMConfig) -> Result<Provider> {
+        let provider: Provider = s.parse()?;
+        if !Self::available(config).contains(&provider) {
+            anyhow::bail!(Self::unknown(s, config));
+        }
+        Ok(provider)
+    }
+
+    /// Error message for a provider that is neither built in nor declared
+    pub(crate) fn unknown(name: &str, config: &LLMConfig) -> String {
+        let available: Vec<_> = Self::available(config)
+            .iter()
+            .map(|p| p.as_str().to_string())
+            .collect();
+        format!(
+            "Unknown provider: {}. Available: {}. Declare custom providers under [llm.providers.<name>] in config.toml",
+            name,
+            available.join(", ")
+        )
+    }
+
+    /// Get all built-in provider names as strings
     pub fn all_as_str() -> Vec<String> {
         Self::all().iter().map(|p| p.as_str().to_string()).collect()
     }
@@ -52,6 +113,15 @@ impl FromStr for Provider {
         match s.to_lowercase().as_str() {
             "openai" => Ok(Provider::OpenAI),
             "anthropic" => Ok(Provider::Anthropic),
+            "azure" => Ok(Provider::AzureOpenAI),
+            "gemini" => Ok(Provider::Gemini),
+            "ollama" => Ok(Provider::Ollama),
+            _ if !s.is_empty()
+                && s.chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') =>
+            {
+                Ok(Provider::Custom(s.to_string()))
+            }
             _ => anyhow::bail!(
                 "Unknown provider: {}. Available: {}",
                 s,
@@ -60,3 +130,16 @@ impl FromStr for Provider {
         }
     }
 }
+
+impl Serialize for Provider {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for Provider {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
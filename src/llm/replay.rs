@@ -0,0 +1,240 @@
+use crate::llm::client::{
+    Backend, ContentPart, LLMClient, LLMMessage, LLMMode, LLMRequest, LLMResponse, ResponseSchema,
+    StreamDelta,
+};
+use crate::system::get_app_dir;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+/// Whether LLM calls are recorded to or replayed from cassette files
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReplayMode {
+    /// Talk to the provider only
+    #[default]
+    Off,
+    /// Talk to the provider and save each request/response pair
+    Record,
+    /// Serve saved responses without any network access
+    Replay,
+}
+
+impl ReplayMode {
+    /// Convert to string representation
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReplayMode::Off => "off",
+            ReplayMode::Record => "record",
+            ReplayMode::Replay => "replay",
+        }
+    }
+}
+
+impl FromStr for ReplayMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "off" | "" => Ok(ReplayMode::Off),
+            "record" => Ok(ReplayMode::Record),
+            "replay" => Ok(ReplayMode::Replay),
+            _ => anyhow::bail!("Invalid replay mode: {}. Use off, record or replay", s),
+        }
+    }
+}
+
+/// Record/replay settings under `[llm.replay]`.
+/// `DOUM_REPLAY` and `DOUM_REPLAY_DIR` take precedence over the config file.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReplayConfig {
+    #[serde(default)]
+    pub mode: ReplayMode,
+    /// Cassette directory (default: `cassettes` in the app directory)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dir: Option<PathBuf>,
+}
+
+impl ReplayConfig {
+    /// Environment variable overriding `mode`
+    pub const MODE_ENV: &'static str = "DOUM_REPLAY";
+    /// Environment variable overriding `dir`
+    pub const DIR_ENV: &'static str = "DOUM_REPLAY_DIR";
+
+    /// Mode in effect, from the environment or the config file
+    pub fn mode(&self) -> Result<ReplayMode> {
+        match std::env::var(Self::MODE_ENV) {
+            Ok(mode) => mode.parse(),
+            Err(_) => Ok(self.mode),
+        }
+    }
+
+    /// Cassette directory in effect, from the environment or the config file
+    pub fn dir(&self) -> Result<PathBuf> {
+        if let Ok(dir) = std::env::var(Self::DIR_ENV) {
+            return Ok(PathBuf::from(dir));
+        }
+        match self.dir {
+            Some(ref dir) => Ok(dir.clone()),
+            None => Ok(get_app_dir()?.join("cassettes")),
+        }
+    }
+
+    /// Whether nothing differs from the defaults (left out of `config.toml`)
+    pub fn is_default(&self) -> bool {
+        self.mode == ReplayMode::Off && self.dir.is_none()
+    }
+}
+
+/// Part of a request that identifies it in a cassette.
+/// The system prompt is left out, as it embeds details of the machine (OS, shell, user).
+#[derive(Debug, Serialize, Deserialize)]
+struct CassetteRequest {
+    mode: LLMMode,
+    messages: Vec<LLMMessage>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    response_schema: Option<ResponseSchema>,
+}
+
+impl CassetteRequest {
+    fn new(request: &LLMRequest) -> Self {
+        Self {
+            mode: request.mode,
+            messages: request
+                .messages
+                .iter()
+                .map(|m| LLMMessage {
+                    role: m.role.clone(),
+                    content: m
+                        .content
+                        .iter()
+                        .map(|part| match part {
+                            ContentPart::Text { text } => ContentPart::Text {
+                                text: text.trim().to_string(),
+                            },
+                            image => image.clone(),
+                        })
+                        .collect(),
+                })
+                .collect(),
+            response_schema: request.response_schema.clone(),
+        }
+    }
+
+    /// File name: the mode followed by a SHA-256 of the normalized request
+    fn file_name(&self) -> Result<String> {
+        let json = serde_json::to_string(self).context("Failed to serialize request")?;
+        let hash: String = Sha256::digest(json.as_bytes())
+            .iter()
+            .take(16)
+            .map(|b| format!("{:02x}", b))
+            .collect();
+        Ok(format!("{}-{}.json", self.mode.as_str(), hash))
+    }
+}
+
+/// Saved request/response pair
+#[derive(Debug, Serialize, Deserialize)]
+struct Cassette {
+    request: CassetteRequest,
+    response: LLMResponse,
+}
+
+/// Client recording provider responses to cassettes, or serving them back
+pub struct ReplayClient {
+    dir: PathBuf,
+    /// Provider backend in record mode; `None` when replaying
+    recorder: Option<Box<Backend>>,
+}
+
+impl ReplayClient {
+    /// Record the responses of `backend` into `dir`
+    pub fn record(backend: Backend, dir: PathBuf) -> Self {
+        Self {
+            dir,
+            recorder: Some(Box::new(backend)),
+        }
+    }
+
+    /// Serve the responses recorded in `dir`
+    pub fn replay(dir: PathBuf) -> Self {
+        Self {
+            dir,
+            recorder: None,
+        }
+    }
+
+    /// Load the recorded response to a request
+    fn load(&self, request: &LLMRequest) -> Result<LLMResponse> {
+        let path = self.dir.join(CassetteRequest::new(request).file_name()?);
+        let content = fs::read_to_string(&path).with_context(|| {
+            format!(
+                "No recorded response for this request ({}). Record it with {}=record",
+                path.display(),
+                ReplayConfig::MODE_ENV
+            )
+        })?;
+        let cassette: Cassette = serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse cassette: {}", path.display()))?;
+
+        // Nothing was spent on a replayed response
+        Ok(LLMResponse {
+            usage: None,
+            ..cassette.response
+        })
+    }
+
+    /// Save a request/response pair
+    fn save(&self, request: &LLMRequest, response: &LLMResponse) -> Result<()> {
+        let request = CassetteRequest::new(request);
+        let path = self.dir.join(request.file_name()?);
+        let cassette = Cassette {
+            request,
+            response: response.clone(),
+        };
+
+        fs::create_dir_all(&self.dir).context("Failed to create cassette directory")?;
+        let content =
+            serde_json::to_string_pretty(&cassette).context("Failed to serialize cassette")?;
+        fs::write(&path, content)
+            .with_context(|| format!("Failed to write cassette: {}", path.display()))?;
+
+        tracing::debug!("Recorded cassette {}", path.display());
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl LLMClient for ReplayClient {
+    async fn generate(&self, request: LLMRequest) -> Result<LLMResponse> {
+        let Some(ref backend) = self.recorder else {
+            return self.load(&request);
+        };
+
+        let response = backend.generate(request.clone()).await?;
+        self.save(&request, &response)?;
+        Ok(response)
+    }
+
+    async fn generate_stream(
+        &self,
+        request: LLMRequest,
+        on_delta: &mut (dyn for<'d> FnMut(StreamDelta<'d>) + Send),
+    ) -> Result<LLMResponse> {
+        let Some(ref backend) = self.recorder else {
+            let response = self.load(&request)?;
+            if let Some(ref reasoning) = response.reasoning {
+                on_delta(StreamDelta::Reasoning(reasoning));
+            }
+            on_delta(StreamDelta::Text(&response.text));
+            return Ok(response);
+        };
+
+        let response = backend.generate_stream(request.clone(), on_delta).await?;
+        self.save(&request, &response)?;
+        Ok(response)
+    }
+}
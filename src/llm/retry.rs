@@ -0,0 +1,266 @@
+use crate::llm::error::LLMError;
+use reqwest::header::HeaderMap;
+use std::fmt;
+use std::time::Duration;
+use time::OffsetDateTime;
+use time::format_description::well_known::Rfc3339;
+
+/// Backoff of the first retry, doubled on each following one
+const BASE_DELAY: Duration = Duration::from_secs(1);
+
+/// Upper bound of the computed backoff
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// Longest server-requested wait that is still honored; longer waits fail immediately
+const MAX_SERVER_WAIT: Duration = Duration::from_secs(120);
+
+/// Rate limit classes reported by the `anthropic-ratelimit-*` headers
+const ANTHROPIC_LIMITS: [&str; 4] = ["requests", "tokens", "input-tokens", "output-tokens"];
+
+/// Retry about to happen, reported so the UI can say what is being waited on
+#[derive(Debug, Clone)]
+pub struct RetryNotice {
+    pub provider: String,
+    pub reason: &'static str,
+    pub delay: Duration,
+    /// Retry number, starting at 1
+    pub retry: u32,
+    pub max_retries: u32,
+}
+
+impl fmt::Display for RetryNotice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {}, retrying in {}s ({}/{})...",
+            self.provider,
+            self.reason,
+            self.delay.as_secs_f64().ceil(),
+            self.retry,
+            self.max_retries
+        )
+    }
+}
+
+/// Decide whether to retry after `error` and how long to wait first.
+/// `retry` is the number of the retry being considered, starting at 1.
+pub(crate) fn plan_retry(
+    error: &anyhow::Error,
+    retry: u32,
+    max_retries: u32,
+) -> Option<RetryNotice> {
+    let llm_error = error.downcast_ref::<LLMError>()?;
+    if !llm_error.is_retryable() || retry > max_retries {
+        return None;
+    }
+
+    let delay = match llm_error.retry_after() {
+        Some(wait) if wait > MAX_SERVER_WAIT => return None,
+        Some(wait) => wait,
+        None => backoff(retry),
+    };
+
+    let reason = match llm_error {
+        LLMError::RateLimited { .. } => "rate limit reached",
+        LLMError::Server { status, .. } if matches!(status.as_u16(), 503 | 529) => "is overloaded",
+        _ => "server error",
+    };
+
+    Some(RetryNotice {
+        provider: llm_error.provider().to_string(),
+        reason,
+        delay,
+        retry,
+        max_retries,
+    })
+}
+
+/// Exponential backoff with jitter: a random delay between half and all of `BASE_DELAY * 2^(retry-1)`
+fn backoff(retry: u32) -> Duration {
+    let exponential = BASE_DELAY
+        .saturating_mul(1 << retry.saturating_sub(1).min(16))
+        .min(MAX_BACKOFF);
+    exponential.mul_f64(0.5 + fastrand::f64() * 0.5)
+}
+
+/// Wait requested by the server through `retry-after(-ms)` or exhausted `anthropic-ratelimit-*` limits
+pub(crate) fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok());
+
+    // Negative, infinite or overflowing values (a misbehaving proxy) are ignored
+    let retry_after = header("retry-after-ms")
+        .and_then(|v| v.trim().parse::<f64>().ok())
+        .and_then(|ms| Duration::try_from_secs_f64(ms.max(0.0) / 1000.0).ok())
+        .or_else(|| {
+            header("retry-after")
+                .and_then(|v| v.trim().parse::<f64>().ok())
+                .and_then(|secs| Duration::try_from_secs_f64(secs.max(0.0)).ok())
+        });
+
+    // Wait for the latest reset among the limits that are used up
+    let now = OffsetDateTime::now_utc();
+    let ratelimit_reset = ANTHROPIC_LIMITS
+        .iter()
+        .filter(|limit| header(&format!("anthropic-ratelimit-{}-remaining", limit)) == Some("0"))
+        .filter_map(|limit| header(&format!("anthropic-ratelimit-{}-reset", limit)))
+        .filter_map(|reset| OffsetDateTime::parse(reset, &Rfc3339).ok())
+        .filter_map(|reset| Duration::try_from(reset - now).ok())
+        .max();
+
+    retry_after.max(ratelimit_reset)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::StatusCode;
+    use reqwest::header::HeaderValue;
+
+    fn headers(pairs: &[(&'static str, String)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.insert(*name, HeaderValue::from_str(value).unwrap());
+        }
+        headers
+    }
+
+    fn rate_limited(retry_after: Option<Duration>) -> anyhow::Error {
+        LLMError::RateLimited {
+            provider: "OpenAI".to_string(),
+            message: "slow down".to_string(),
+            retry_after,
+        }
+        .into()
+    }
+
+    #[test]
+    fn retry_after_prefers_milliseconds() {
+        let headers = headers(&[
+            ("retry-after-ms", "1500".to_string()),
+            ("retry-after", "7".to_string()),
+        ]);
+        assert_eq!(retry_after(&headers), Some(Duration::from_millis(1500)));
+    }
+
+    #[test]
+    fn retry_after_reads_seconds() {
+        let headers = headers(&[("retry-after", " 3 ".to_string())]);
+        assert_eq!(retry_after(&headers), Some(Duration::from_secs(3)));
+        assert_eq!(retry_after(&HeaderMap::new()), None);
+    }
+
+    #[test]
+    fn retry_after_ignores_http_dates() {
+        let headers = headers(&[("retry-after", "Wed, 21 Oct 2015 07:28:00 GMT".to_string())]);
+        assert_eq!(retry_after(&headers), None);
+    }
+
+    #[test]
+    fn retry_after_ignores_huge_and_infinite_values() {
+        for value in ["1e400", "inf", "1e300"] {
+            let headers = headers(&[("retry-after", value.to_string())]);
+            assert_eq!(retry_after(&headers), None, "retry-after: {}", value);
+        }
+        let headers = headers(&[
+            ("retry-after-ms", "1e30".to_string()),
+            ("retry-after", "5".to_string()),
+        ]);
+        assert_eq!(retry_after(&headers), Some(Duration::from_secs(5)));
+    }
+
+    #[test]
+    fn retry_after_waits_for_exhausted_anthropic_limits_only() {
+        let reset = |secs: i64| {
+            (OffsetDateTime::now_utc() + time::Duration::seconds(secs))
+                .format(&Rfc3339)
+                .unwrap()
+        };
+        let headers = headers(&[
+            ("anthropic-ratelimit-requests-remaining", "0".to_string()),
+            ("anthropic-ratelimit-requests-reset", reset(20)),
+            ("anthropic-ratelimit-tokens-remaining", "5000".to_string()),
+            ("anthropic-ratelimit-tokens-reset", reset(90)),
+            ("retry-after", "2".to_string()),
+        ]);
+
+        let wait = retry_after(&headers).unwrap();
+        assert!(wait > Duration::from_secs(15) && wait <= Duration::from_secs(20));
+    }
+
+    #[test]
+    fn retry_after_ignores_resets_in_the_past() {
+        let past = (OffsetDateTime::now_utc() - time::Duration::seconds(30))
+            .format(&Rfc3339)
+            .unwrap();
+        let headers = headers(&[
+            ("anthropic-ratelimit-tokens-remaining", "0".to_string()),
+            ("anthropic-ratelimit-tokens-reset", past),
+        ]);
+        assert_eq!(retry_after(&headers), None);
+    }
+
+    #[test]
+    fn plan_retry_honors_server_wait() {
+        let notice = plan_retry(&rate_limited(Some(Duration::from_secs(4))), 1, 3).unwrap();
+        assert_eq!(notice.delay, Duration::from_secs(4));
+        assert_eq!(notice.reason, "rate limit reached");
+        assert_eq!(notice.provider, "OpenAI");
+    }
+
+    #[test]
+    fn plan_retry_gives_up_on_long_server_wait() {
+        assert!(plan_retry(&rate_limited(Some(MAX_SERVER_WAIT * 2)), 1, 3).is_none());
+    }
+
+    #[test]
+    fn plan_retry_stops_after_max_retries() {
+        assert!(plan_retry(&rate_limited(None), 3, 3).is_some());
+        assert!(plan_retry(&rate_limited(None), 4, 3).is_none());
+    }
+
+    #[test]
+    fn plan_retry_skips_permanent_errors() {
+        let auth: anyhow::Error = LLMError::Auth {
+            provider: "OpenAI".to_string(),
+            message: "bad key".to_string(),
+        }
+        .into();
+        let overloaded: anyhow::Error = LLMError::Server {
+            provider: "Anthropic".to_string(),
+            status: StatusCode::from_u16(529).unwrap(),
+            message: "overloaded".to_string(),
+            retry_after: None,
+        }
+        .into();
+        let not_implemented: anyhow::Error = LLMError::Server {
+            provider: "Anthropic".to_string(),
+            status: StatusCode::NOT_IMPLEMENTED,
+            message: "nope".to_string(),
+            retry_after: None,
+        }
+        .into();
+
+        assert!(plan_retry(&auth, 1, 3).is_none());
+        assert!(plan_retry(&anyhow::anyhow!("plain error"), 1, 3).is_none());
+        assert!(plan_retry(&not_implemented, 1, 3).is_none());
+        assert_eq!(
+            plan_retry(&overloaded, 1, 3).unwrap().reason,
+            "is overloaded"
+        );
+    }
+
+    #[test]
+    fn backoff_doubles_within_jitter_and_is_capped() {
+        for retry in 1..=4 {
+            let full = BASE_DELAY * (1 << (retry - 1));
+            let delay = backoff(retry);
+            assert!(
+                delay >= full / 2 && delay <= full,
+                "retry {}: {:?}",
+                retry,
+                delay
+            );
+        }
+        assert!(backoff(30) <= MAX_BACKOFF);
+    }
+}
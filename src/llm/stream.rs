@@ -0,0 +1,164 @@
+use crate::llm::error::LLMError;
+use anyhow::Result;
+use reqwest::Response;
+use std::time::Duration;
+
+/// Server-sent event
+#[derive(Debug, Clone, Default)]
+pub(crate) struct SseEvent {
+    pub event: Option<String>,
+    pub data: String,
+}
+
+/// Incremental parser for `text/event-stream` bodies
+#[derive(Debug, Default)]
+pub(crate) struct SseParser {
+    /// Bytes of the unfinished line; chunks may end inside a multi-byte character
+    buffer: Vec<u8>,
+    current: SseEvent,
+}
+
+impl SseParser {
+    /// Feed a chunk of bytes and return the events completed by it
+    pub fn feed(&mut self, chunk: &[u8]) -> Vec<SseEvent> {
+        self.buffer.extend_from_slice(chunk);
+
+        let mut events = Vec::new();
+        while let Some(newline) = self.buffer.iter().position(|&b| b == b'\n') {
+            let bytes: Vec<u8> = self.buffer.drain(..=newline).collect();
+            let line = String::from_utf8_lossy(&bytes);
+            let line = line.trim_end_matches(['\n', '\r']);
+
+            // Blank line dispatches the pending event
+            if line.is_empty() {
+                if !self.current.data.is_empty() || self.current.event.is_some() {
+                    events.push(std::mem::take(&mut self.current));
+                }
+                continue;
+            }
+
+            // Comment line
+            if line.starts_with(':') {
+                continue;
+            }
+
+            let (field, value) = match line.split_once(':') {
+                Some((field, value)) => (field, value.strip_prefix(' ').unwrap_or(value)),
+                None => (line, ""),
+            };
+
+            match field {
+                "event" => self.current.event = Some(value.to_string()),
+                "data" => {
+                    if !self.current.data.is_empty() {
+                        self.current.data.push('\n');
+                    }
+                    self.current.data.push_str(value);
+                }
+                _ => {}
+            }
+        }
+
+        events
+    }
+}
+
+/// Read server-sent events from a streaming response.
+/// Fails when no data arrives for `idle_timeout`.
+pub(crate) async fn read_events<F>(
+    mut response: Response,
+    provider: &str,
+    idle_timeout: Duration,
+    mut on_event: F,
+) -> Result<()>
+where
+    F: FnMut(SseEvent) -> Result<()>,
+{
+    let mut parser = SseParser::default();
+
+    loop {
+        let chunk = tokio::time::timeout(idle_timeout, response.chunk())
+            .await
+            .map_err(|_| LLMError::Timeout {
+                provider: provider.to_string(),
+                message: format!(
+                    "no data received for {}s while streaming",
+                    idle_timeout.as_secs()
+                ),
+            })?
+            .map_err(|e| LLMError::Network {
+                provider: provider.to_string(),
+                message: format!("failed to read response stream: {}", e),
+            })?;
+
+        let Some(chunk) = chunk else {
+            break;
+        };
+
+        for event in parser.feed(&chunk) {
+            on_event(event)?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dispatches_event_on_blank_line() {
+        let mut parser = SseParser::default();
+        let events = parser.feed(b"event: delta\ndata: {\"a\":1}\n\n");
+
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].event.as_deref(), Some("delta"));
+        assert_eq!(events[0].data, r#"{"a":1}"#);
+    }
+
+    #[test]
+    fn joins_multiple_data_lines() {
+        let mut parser = SseParser::default();
+        let events = parser.feed(b"data: first\ndata: second\n\n");
+
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].event, None);
+        assert_eq!(events[0].data, "first\nsecond");
+    }
+
+    #[test]
+    fn skips_comments_and_handles_crlf() {
+        let mut parser = SseParser::default();
+        let events = parser.feed(b": keep-alive\r\ndata:no-space\r\n\r\n");
+
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].data, "no-space");
+    }
+
+    #[test]
+    fn waits_for_lines_split_across_chunks() {
+        let mut parser = SseParser::default();
+
+        assert!(parser.feed(b"data: hel").is_empty());
+        assert!(parser.feed(b"lo\n").is_empty());
+        let events = parser.feed(b"\n");
+
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].data, "hello");
+    }
+
+    #[test]
+    fn keeps_multi_byte_character_split_across_chunks() {
+        let mut parser = SseParser::default();
+        let bytes = "data: 안녕 👋\n\n".as_bytes();
+        // Split inside the three-byte '안'
+        let split = "data: ".len() + 1;
+
+        assert!(parser.feed(&bytes[..split]).is_empty());
+        let events = parser.feed(&bytes[split..]);
+
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].data, "안녕 👋");
+    }
+}
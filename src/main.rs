@@ -1,33 +1,43 @@
 use anyhow::Result;
 use clap::Parser;
 use doum_cli::cli::{
-    Cli, Commands, handle_ask_command, handle_auto_command, handle_config_command,
-    handle_secret_command, handle_suggest_command, handle_switch_command,
+    Cli, Commands, handle_ask_command, handle_auto_command, handle_chat_command,
+    handle_config_command, handle_explain_command, handle_fix_command, handle_secret_command,
+    handle_sessions_command, handle_suggest_command, handle_switch_command, handle_usage_command,
+    print_fix_hook, resume_session,
 };
+use doum_cli::llm::LLMError;
 use doum_cli::system::{init_logging, load_config, load_default_config};
 
 #[tokio::main]
 async fn main() {
     let exit_code = match run().await {
-        Ok(_) => 0,
+        // Exit status of an executed command, 0 otherwise
+        Ok(code) => code,
         Err(e) => {
             // Internal logging
             tracing::error!("doum-cli terminated with an error: {}", e);
 
-            // User-facing error message
+            // User-facing error message, with a hint and a distinct exit code for LLM errors
             eprintln!("\n[Error] {}\n", e);
-            1
+            match e.chain().find_map(|c| c.downcast_ref::<LLMError>()) {
+                Some(llm_error) => {
+                    eprintln!("💡 {}\n", llm_error.hint());
+                    llm_error.exit_code()
+                }
+                None => 1,
+            }
         }
     };
 
     std::process::exit(exit_code);
 }
 
-async fn run() -> Result<()> {
+async fn run() -> Result<i32> {
     let cli = Cli::parse();
 
     // Load configuration
-    let config = load_config().unwrap_or_else(|e| {
+    let mut config = load_config().unwrap_or_else(|e| {
         eprintln!(
             "⚠️  Failed to load configuration: {}. Falling back to default configuration.",
             e
@@ -35,6 +45,11 @@ async fn run() -> Result<()> {
         load_default_config().expect("Failed to load default configuration")
     });
 
+    // Apply per-invocation overrides
+    if let Some(web_search) = cli.web_search_override() {
+        config.llm.use_web_search = web_search;
+    }
+
     // Initialize logging
     if let Err(e) = init_logging(&config) {
         eprintln!(
@@ -49,35 +64,82 @@ async fn run() -> Result<()> {
         Some(Commands::Config { action }) => {
             tracing::info!("Running 'config' command");
             handle_config_command(action)?;
-            Ok(())
+            Ok(0)
         }
         Some(Commands::Secret) => {
             tracing::info!("Running 'secret' command");
             handle_secret_command().await?;
-            Ok(())
+            Ok(0)
         }
-        Some(Commands::Switch) => {
+        Some(Commands::Switch {
+            provider,
+            model,
+            refresh,
+            force,
+        }) => {
             tracing::info!("Running 'switch' command");
-            handle_switch_command().await?;
-            Ok(())
+            handle_switch_command(provider, model, refresh, force).await?;
+            Ok(0)
         }
-        Some(Commands::Ask { question }) => {
+        Some(Commands::Ask {
+            question,
+            images,
+            continue_session,
+            session,
+        }) => {
             tracing::info!("Running 'ask' command with question: {}", question);
-            handle_ask_command(&question).await
+            let session = resume_session(continue_session, session)?;
+            handle_ask_command(&config, &question, &images, session).await?;
+            Ok(0)
         }
-        Some(Commands::Suggest { request }) => {
+        Some(Commands::Suggest { request, exec }) => {
             tracing::info!("Running 'suggest' command with request: {}", request);
-            handle_suggest_command(&request).await
+            handle_suggest_command(&config, &request, exec).await
+        }
+        Some(Commands::Chat) => {
+            tracing::info!("Running 'chat' command");
+            handle_chat_command(&config).await?;
+            Ok(0)
+        }
+        Some(Commands::Explain { command }) => {
+            tracing::info!("Running 'explain' command with command: {}", command);
+            handle_explain_command(&config, &command).await?;
+            Ok(0)
+        }
+        Some(Commands::Fix {
+            hook: Some(shell), ..
+        }) => {
+            print_fix_hook(&shell)?;
+            Ok(0)
+        }
+        Some(Commands::Fix {
+            command,
+            exit_code,
+            stderr,
+            hook: None,
+        }) => {
+            tracing::info!("Running 'fix' command");
+            handle_fix_command(&config, command, exit_code, stderr).await
+        }
+        Some(Commands::Sessions { action }) => {
+            tracing::info!("Running 'sessions' command");
+            handle_sessions_command(action)?;
+            Ok(0)
+        }
+        Some(Commands::Usage { month }) => {
+            tracing::info!("Running 'usage' command");
+            handle_usage_command(&config.budget, month)?;
+            Ok(0)
         }
         None => {
             if let Some(input) = cli.input {
                 tracing::info!("Running 'auto' mode with input: {}", input);
-                handle_auto_command(&input).await
+                handle_auto_command(&config, &input).await
             } else {
                 // No arguments: show help and exit
                 tracing::info!("doum-cli invoked without arguments. Showing help and exiting.");
                 Cli::parse_from(["doum", "--help"]);
-                Ok(())
+                Ok(0)
             }
         }
     };
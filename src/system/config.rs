@@ -1,7 +1,11 @@
-use crate::llm::Provider;
+use crate::llm::{
+    CustomProviderConfig, GenerationParams, LLMMode, ModelPricing, OllamaClient, Provider,
+    ReplayConfig,
+};
 use crate::system::paths::get_config_path;
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::PathBuf;
 
@@ -14,6 +18,12 @@ pub struct Config {
     pub llm: LLMConfig,
     pub context: ContextConfig,
     pub logging: LoggingConfig,
+    #[serde(default)]
+    pub budget: BudgetConfig,
+    #[serde(default)]
+    pub network: NetworkConfig,
+    #[serde(default)]
+    pub modes: ModesConfig,
 }
 
 /// Configuration for LLM API
@@ -24,6 +34,35 @@ pub struct LLMConfig {
     pub timeout: u64,
     pub use_thinking: bool,
     pub use_web_search: bool,
+    /// Retries after rate limits and transient server errors
+    #[serde(default = "default_max_retries")]
+    pub max_retries: u32,
+    #[serde(default = "default_ollama_url")]
+    pub ollama_url: String,
+    /// Record/replay responses to cassettes for offline runs
+    #[serde(default, skip_serializing_if = "ReplayConfig::is_default")]
+    pub replay: ReplayConfig,
+    /// Provider/model pairs tried in order when the primary is unavailable
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fallbacks: Vec<FallbackConfig>,
+    /// User-defined OpenAI-compatible providers keyed by name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub providers: BTreeMap<String, CustomProviderConfig>,
+}
+
+/// Fallback provider and model
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FallbackConfig {
+    pub provider: Provider,
+    pub model: String,
+}
+
+fn default_max_retries() -> u32 {
+    3
+}
+
+fn default_ollama_url() -> String {
+    OllamaClient::DEFAULT_BASE_URL.to_string()
 }
 
 /// Context management settings
@@ -40,6 +79,114 @@ pub struct LoggingConfig {
     pub level: String,
 }
 
+/// Spending limits, checked against the usage ledger before each request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BudgetConfig {
+    /// Spending limit in USD per day (UTC)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub daily_usd: Option<f64>,
+    /// Spending limit in USD per calendar month (UTC)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub monthly_usd: Option<f64>,
+    /// Warn when a request's estimated input tokens exceed this
+    #[serde(default = "default_warn_input_tokens")]
+    pub warn_input_tokens: u64,
+    /// Input price in USD per million tokens for models without preset prices
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_input_price: Option<f64>,
+    /// Output price in USD per million tokens for models without preset prices
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_output_price: Option<f64>,
+}
+
+impl BudgetConfig {
+    /// Whether a daily or monthly limit is set
+    pub fn has_limit(&self) -> bool {
+        self.daily_usd.is_some() || self.monthly_usd.is_some()
+    }
+
+    /// Prices applied to models without preset prices, once both are set
+    pub fn default_price(&self) -> Option<ModelPricing> {
+        Some(ModelPricing {
+            input: self.default_input_price?,
+            cached_input: None,
+            cache_write: None,
+            output: self.default_output_price?,
+        })
+    }
+}
+
+impl Default for BudgetConfig {
+    fn default() -> Self {
+        Self {
+            daily_usd: None,
+            monthly_usd: None,
+            warn_input_tokens: default_warn_input_tokens(),
+            default_input_price: None,
+            default_output_price: None,
+        }
+    }
+}
+
+fn default_warn_input_tokens() -> u64 {
+    20_000
+}
+
+/// Generation settings per mode (`[modes.ask]`, `[modes.suggest]`, `[modes.auto]`, ...)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ModesConfig {
+    #[serde(default, skip_serializing_if = "GenerationParams::is_default")]
+    pub ask: GenerationParams,
+    #[serde(default, skip_serializing_if = "GenerationParams::is_default")]
+    pub suggest: GenerationParams,
+    #[serde(default, skip_serializing_if = "GenerationParams::is_default")]
+    pub auto: GenerationParams,
+    #[serde(default, skip_serializing_if = "GenerationParams::is_default")]
+    pub explain: GenerationParams,
+    #[serde(default, skip_serializing_if = "GenerationParams::is_default")]
+    pub fix: GenerationParams,
+    #[serde(default, skip_serializing_if = "GenerationParams::is_default")]
+    pub chat: GenerationParams,
+}
+
+impl ModesConfig {
+    /// Settings for a mode (provider defaults for API key verification)
+    pub fn for_mode(&self, mode: LLMMode) -> GenerationParams {
+        match mode {
+            LLMMode::Ask => self.ask,
+            LLMMode::Suggest => self.suggest,
+            LLMMode::Auto => self.auto,
+            LLMMode::Explain => self.explain,
+            LLMMode::Fix => self.fix,
+            LLMMode::Chat => self.chat,
+            LLMMode::Verify => GenerationParams::default(),
+        }
+    }
+}
+
+/// HTTP settings shared by every provider client
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NetworkConfig {
+    /// Proxy for all requests (e.g., `http://proxy.corp:3128`); `HTTPS_PROXY` and friends apply otherwise
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<String>,
+    /// Comma-separated hosts, domains or CIDR blocks that bypass `proxy`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub no_proxy: Option<String>,
+    /// PEM files with extra root certificates to trust (e.g., an internal CA)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ca_certs: Vec<PathBuf>,
+    /// PEM client certificate for mutual TLS
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_cert: Option<PathBuf>,
+    /// PKCS#8 PEM private key of `client_cert`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_key: Option<PathBuf>,
+    /// Connection timeout in seconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub connect_timeout: Option<u64>,
+}
+
 /// Ensure configuration directory and return config file path
 fn ensure_config() -> Result<PathBuf> {
     let config_path = get_config_path()?;
@@ -89,6 +236,11 @@ pub fn load_default_config() -> Result<Config> {
             timeout: 30,
             use_thinking: false,
             use_web_search: true,
+            max_retries: default_max_retries(),
+            ollama_url: default_ollama_url(),
+            replay: ReplayConfig::default(),
+            fallbacks: Vec::new(),
+            providers: BTreeMap::new(),
         },
         context: ContextConfig {
             max_lines: 100,
@@ -98,6 +250,9 @@ pub fn load_default_config() -> Result<Config> {
             enabled: true,
             level: "info".to_string(),
         },
+        budget: BudgetConfig::default(),
+        network: NetworkConfig::default(),
+        modes: ModesConfig::default(),
     })
 }
 
@@ -5,11 +5,21 @@ pub mod env;
 pub mod logging;
 pub mod paths;
 pub mod secret;
+pub mod session;
+pub mod spool;
+pub mod usage;
 
 pub use config::{
-    Config, ContextConfig, LLMConfig, LoggingConfig, load_config, load_default_config, save_config,
+    BudgetConfig, Config, ContextConfig, FallbackConfig, LLMConfig, LoggingConfig, ModesConfig,
+    NetworkConfig, load_config, load_default_config, save_config,
 };
 pub use env::{OsType, ShellType, SystemInfo, detect_os, detect_shell, get_system_info};
 pub use logging::init_logging;
-pub use paths::{get_app_dir, get_config_path, get_log_dir};
+pub use paths::{
+    get_app_dir, get_cache_dir, get_config_path, get_log_dir, get_sessions_dir, get_spool_path,
+    get_usage_path,
+};
 pub use secret::{ProviderSecret, SecretManager};
+pub use session::{Session, list_sessions, messages_to_markdown};
+pub use spool::{LastCommand, load_last_command};
+pub use usage::{UsageRecord, append_usage, load_usage};
@@ -38,6 +38,26 @@ pub fn get_log_dir() -> Result<PathBuf> {
     Ok(get_app_dir()?.join("logs"))
 }
 
+/// Returns the cache directory path
+pub fn get_cache_dir() -> Result<PathBuf> {
+    Ok(get_app_dir()?.join("cache"))
+}
+
+/// Returns the token usage ledger path
+pub fn get_usage_path() -> Result<PathBuf> {
+    Ok(get_app_dir()?.join("usage.jsonl"))
+}
+
+/// Returns the path where the shell hook records the last command (for `doum fix`)
+pub fn get_spool_path() -> Result<PathBuf> {
+    Ok(get_app_dir()?.join("last_command"))
+}
+
+/// Returns the directory holding saved conversation sessions
+pub fn get_sessions_dir() -> Result<PathBuf> {
+    Ok(get_app_dir()?.join("sessions"))
+}
+
 /// Returns the configuration file path
 pub fn get_config_path() -> Result<PathBuf> {
     Ok(get_app_dir()?.join("config.toml"))
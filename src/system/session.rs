@@ -0,0 +1,196 @@
+use crate::llm::{ContentPart, LLMMessage, LLMRole, Provider};
+use crate::system::paths::get_sessions_dir;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use time::OffsetDateTime;
+
+#[cfg(unix)]
+use std::os::unix::fs::PermissionsExt;
+
+/// Longest session title, in characters
+const TITLE_LENGTH: usize = 60;
+
+/// Conversation saved as `<id>.json` in the sessions directory
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Session {
+    pub id: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    pub updated_at: OffsetDateTime,
+    /// Provider and model configured for the last exchange
+    pub provider: Provider,
+    pub model: String,
+    pub messages: Vec<LLMMessage>,
+}
+
+impl Session {
+    /// Start an empty session, identified by its creation time and a random suffix
+    pub fn new(provider: Provider, model: String) -> Self {
+        let now = OffsetDateTime::now_utc();
+        let id = format!(
+            "{:04}{:02}{:02}-{:02}{:02}{:02}-{:04x}",
+            now.year(),
+            now.month() as u8,
+            now.day(),
+            now.hour(),
+            now.minute(),
+            now.second(),
+            fastrand::u16(..)
+        );
+
+        Self {
+            id,
+            created_at: now,
+            updated_at: now,
+            provider,
+            model,
+            messages: Vec::new(),
+        }
+    }
+
+    /// Load a session by id, or by a prefix matching a single session
+    pub fn load(id: &str) -> Result<Self> {
+        let mut matches: Vec<_> = list_sessions()?
+            .into_iter()
+            .filter(|s| s.id.starts_with(id))
+            .collect();
+
+        if let Some(exact) = matches.iter().position(|s| s.id == id) {
+            return Ok(matches.swap_remove(exact));
+        }
+        match matches.len() {
+            0 => anyhow::bail!(
+                "Session not found: {}. List sessions with `doum sessions list`",
+                id
+            ),
+            1 => Ok(matches.remove(0)),
+            n => anyhow::bail!("{} sessions start with {}. Use a longer id", n, id),
+        }
+    }
+
+    /// The most recently updated session
+    pub fn latest() -> Result<Option<Self>> {
+        Ok(list_sessions()?.into_iter().next())
+    }
+
+    /// Write the session, marking it updated now
+    pub fn save(&mut self) -> Result<()> {
+        self.updated_at = OffsetDateTime::now_utc();
+
+        let dir = get_sessions_dir()?;
+        fs::create_dir_all(&dir).context("Failed to create sessions directory")?;
+
+        // Conversations may contain private data; set directory permissions to 700 on Unix
+        #[cfg(unix)]
+        fs::set_permissions(&dir, fs::Permissions::from_mode(0o700))
+            .context("Failed to set sessions directory permissions")?;
+
+        let path = session_path(&self.id)?;
+        let content = serde_json::to_string_pretty(self).context("Failed to serialize session")?;
+        fs::write(&path, content)
+            .with_context(|| format!("Failed to write session: {}", path.display()))?;
+
+        // and file permissions to 600
+        #[cfg(unix)]
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o600))
+            .context("Failed to set session file permissions")?;
+
+        tracing::debug!("Saved session {}", self.id);
+        Ok(())
+    }
+
+    /// Delete the session file
+    pub fn delete(&self) -> Result<()> {
+        let path = session_path(&self.id)?;
+        fs::remove_file(&path)
+            .with_context(|| format!("Failed to delete session: {}", path.display()))
+    }
+
+    /// First line of the first question, shortened
+    pub fn title(&self) -> String {
+        let first = self
+            .messages
+            .iter()
+            .find(|m| m.role == LLMRole::User)
+            .map(|m| m.text())
+            .unwrap_or_default();
+        let line = first.lines().next().unwrap_or_default().trim();
+
+        if line.chars().count() > TITLE_LENGTH {
+            let short: String = line.chars().take(TITLE_LENGTH - 1).collect();
+            format!("{}…", short)
+        } else {
+            line.to_string()
+        }
+    }
+
+    /// Markdown document with a heading per message
+    pub fn to_markdown(&self) -> String {
+        let mut content = format!(
+            "# {}\n\nSession {} with {}/{}, started {} (UTC)\n",
+            self.title(),
+            self.id,
+            self.provider.as_str(),
+            self.model,
+            self.created_at.date()
+        );
+        content.push_str(&messages_to_markdown(&self.messages));
+        content
+    }
+}
+
+/// Messages as Markdown sections (`## You` / `## Assistant`); images are noted by type
+pub fn messages_to_markdown(messages: &[LLMMessage]) -> String {
+    let mut content = String::new();
+    for message in messages {
+        let heading = match message.role {
+            LLMRole::User => "You",
+            LLMRole::Assistant => "Assistant",
+        };
+        content.push_str(&format!("\n## {}\n\n", heading));
+
+        for part in &message.content {
+            match part {
+                ContentPart::Text { text } => content.push_str(&format!("{}\n", text.trim())),
+                ContentPart::Image(image) => {
+                    content.push_str(&format!("*[{} image]*\n\n", image.media_type))
+                }
+            }
+        }
+    }
+    content
+}
+
+/// All saved sessions, most recently updated first; unreadable files are skipped
+pub fn list_sessions() -> Result<Vec<Session>> {
+    let dir = get_sessions_dir()?;
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut sessions = Vec::new();
+    for entry in fs::read_dir(&dir).context("Failed to read sessions directory")? {
+        let path = entry?.path();
+        if path.extension().is_none_or(|ext| ext != "json") {
+            continue;
+        }
+
+        let session = fs::read_to_string(&path)
+            .map_err(anyhow::Error::from)
+            .and_then(|content| Ok(serde_json::from_str::<Session>(&content)?));
+        match session {
+            Ok(session) => sessions.push(session),
+            Err(e) => tracing::warn!("Skipping invalid session {}: {}", path.display(), e),
+        }
+    }
+
+    sessions.sort_by_key(|s| std::cmp::Reverse(s.updated_at));
+    Ok(sessions)
+}
+
+fn session_path(id: &str) -> Result<PathBuf> {
+    Ok(get_sessions_dir()?.join(format!("{}.json", id)))
+}
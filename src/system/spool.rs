@@ -0,0 +1,39 @@
+use crate::system::paths::get_spool_path;
+use anyhow::{Context, Result};
+use std::fs;
+
+/// Last command run in the shell, as recorded by the `doum fix --hook` shell hook.
+/// The spool file holds the exit code on the first line and the command after it.
+#[derive(Debug, Clone)]
+pub struct LastCommand {
+    pub command: String,
+    pub exit_code: i32,
+}
+
+/// Load the last recorded command, `None` when the hook has not written one
+pub fn load_last_command() -> Result<Option<LastCommand>> {
+    let path = get_spool_path()?;
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let content =
+        fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let Some((code, command)) = content.split_once('\n') else {
+        return Ok(None);
+    };
+
+    let exit_code = code
+        .trim()
+        .parse()
+        .with_context(|| format!("Invalid exit code in {}: {}", path.display(), code))?;
+    let command = command.trim();
+    if command.is_empty() {
+        return Ok(None);
+    }
+
+    Ok(Some(LastCommand {
+        command: command.to_string(),
+        exit_code,
+    }))
+}
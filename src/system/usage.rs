@@ -0,0 +1,95 @@
+use crate::llm::{LLMMode, Provider, Usage};
+use crate::system::paths::get_usage_path;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use time::OffsetDateTime;
+
+#[cfg(unix)]
+use std::os::unix::fs::PermissionsExt;
+
+/// Token usage of a single LLM call, one JSON line in the usage ledger
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageRecord {
+    #[serde(with = "time::serde::rfc3339")]
+    pub timestamp: OffsetDateTime,
+    pub provider: Provider,
+    pub model: String,
+    pub mode: LLMMode,
+    #[serde(flatten)]
+    pub usage: Usage,
+}
+
+impl UsageRecord {
+    /// Create a record for a call finished now
+    pub fn now(provider: Provider, model: String, mode: LLMMode, usage: Usage) -> Self {
+        Self {
+            timestamp: OffsetDateTime::now_utc(),
+            provider,
+            model,
+            mode,
+            usage,
+        }
+    }
+}
+
+/// Append a record to the usage ledger
+pub fn append_usage(record: &UsageRecord) -> Result<()> {
+    let usage_path = get_usage_path()?;
+
+    // Create app directory if it doesn't exist
+    if let Some(parent) = usage_path.parent()
+        && !parent.exists()
+    {
+        fs::create_dir_all(parent).context("Failed to create app directory")?;
+    }
+
+    let line = serde_json::to_string(record).context("Failed to serialize usage record")?;
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&usage_path)
+        .context("Failed to open usage ledger")?;
+    writeln!(file, "{}", line).context("Failed to write usage ledger")?;
+
+    // The ledger reveals which models are used and when; set permissions to 600 on Unix
+    #[cfg(unix)]
+    {
+        let mut permissions = file
+            .metadata()
+            .context("Failed to read usage ledger metadata")?
+            .permissions();
+        if permissions.mode() & 0o777 != 0o600 {
+            permissions.set_mode(0o600);
+            fs::set_permissions(&usage_path, permissions)
+                .context("Failed to set usage ledger permissions")?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Load all records from the usage ledger, skipping lines that cannot be read
+pub fn load_usage() -> Result<Vec<UsageRecord>> {
+    let usage_path = get_usage_path()?;
+
+    if !usage_path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let content = fs::read_to_string(&usage_path).context("Failed to read usage ledger")?;
+    let records = content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| match serde_json::from_str(line) {
+            Ok(record) => Some(record),
+            Err(e) => {
+                tracing::warn!("Skipping invalid usage record: {}", e);
+                None
+            }
+        })
+        .collect();
+
+    Ok(records)
+}
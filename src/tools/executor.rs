@@ -1,7 +1,9 @@
 use crate::system::env::{OsType, ShellType, SystemInfo};
 use anyhow::{Context, Result};
+use std::io::Read;
 use std::process::{Command, Output, Stdio};
-use std::thread;
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
 use std::time::{Duration, Instant};
 
 /// Result of command execution
@@ -144,30 +146,29 @@ fn run_with_timeout(mut cmd: Command, timeout: Option<Duration>) -> Result<Outpu
                 .context("Failed to wait for command")
         }
         Some(timeout) => {
+            // Read the pipes while waiting: a command writing more than the pipe buffer
+            // would otherwise block on it until the timeout
+            let (stdout, stdout_reader) = drain(child.stdout.take());
+            let (stderr, stderr_reader) = drain(child.stderr.take());
             let start = Instant::now();
 
-            loop {
+            let status = loop {
                 match child.try_wait() {
                     // Process finished
-                    Ok(Some(_status)) => {
-                        return child
-                            .wait_with_output()
-                            .context("Failed to wait for command output");
-                    }
+                    Ok(Some(status)) => break status,
                     // Still running
                     Ok(None) => {
                         if start.elapsed() >= timeout {
                             // When timeout occurs, kill the process
                             let _ = child.kill();
-                            let output = child
-                                .wait_with_output()
-                                .context("Failed to collect output after killing command")?;
+                            let _ = child.wait();
 
-                            // Return timeout error with partial output
+                            // Return timeout error with partial output. The readers are not
+                            // joined, as processes started by the command may keep the pipes open.
                             anyhow::bail!(
                                 "Command timed out after {:?}. Partial output:\n{}",
                                 timeout,
-                                String::from_utf8_lossy(&output.stdout)
+                                String::from_utf8_lossy(&stdout.lock().unwrap())
                             );
                         }
 
@@ -178,7 +179,33 @@ fn run_with_timeout(mut cmd: Command, timeout: Option<Duration>) -> Result<Outpu
                         anyhow::bail!("Failed to poll command status: {}", e);
                     }
                 }
-            }
+            };
+
+            let _ = stdout_reader.join();
+            let _ = stderr_reader.join();
+            Ok(Output {
+                status,
+                stdout: std::mem::take(&mut stdout.lock().unwrap()),
+                stderr: std::mem::take(&mut stderr.lock().unwrap()),
+            })
         }
     }
 }
+
+/// Read a pipe to the end on a separate thread, into a buffer readable at any time
+fn drain(pipe: Option<impl Read + Send + 'static>) -> (Arc<Mutex<Vec<u8>>>, JoinHandle<()>) {
+    let buffer = Arc::new(Mutex::new(Vec::new()));
+    let reader = thread::spawn({
+        let buffer = Arc::clone(&buffer);
+        move || {
+            let Some(mut pipe) = pipe else { return };
+            let mut chunk = [0u8; 8192];
+            while let Ok(n) = pipe.read(&mut chunk)
+                && n > 0
+            {
+                buffer.lock().unwrap().extend_from_slice(&chunk[..n]);
+            }
+        }
+    });
+    (buffer, reader)
+}
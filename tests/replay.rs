@@ -0,0 +1,151 @@
+//! End-to-end runs of the `doum` binary against the cassettes in `tests/cassettes`.
+//!
+//! Each test runs with `DOUM_REPLAY=replay`, so no provider or API key is needed,
+//! in its own app directory and without a terminal (suggestions are printed, not selected).
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+
+/// Fresh app directory for one test
+fn app_root(name: &str) -> PathBuf {
+    let root = std::env::temp_dir().join(format!("doum-test-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    fs::create_dir_all(&root).unwrap();
+    root
+}
+
+/// Run `doum` with the given arguments, replaying the committed cassettes
+fn doum(root: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_doum"))
+        .args(args)
+        .env("HOME", root)
+        .env("XDG_CONFIG_HOME", root)
+        .env("APPDATA", root)
+        .env("DOUM_REPLAY", "replay")
+        .env(
+            "DOUM_REPLAY_DIR",
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/cassettes"),
+        )
+        .stdin(Stdio::null())
+        .output()
+        .expect("failed to run doum")
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).to_string()
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).to_string()
+}
+
+/// Session files saved below `dir`, wherever the platform puts the app directory
+fn session_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir).into_iter().flatten().flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            files.extend(session_files(&path));
+        } else if path.parent().is_some_and(|p| p.ends_with("sessions")) {
+            files.push(path);
+        }
+    }
+    files
+}
+
+#[test]
+fn suggest_prints_commands_and_saves_session() {
+    let root = app_root("suggest");
+    let output = doum(&root, &["suggest", "find what is using disk space here"]);
+
+    assert_eq!(output.status.code(), Some(0), "{}", stderr(&output));
+    let stdout = stdout(&output);
+    assert!(stdout.contains("du -sh * | sort -rh | head -n 10  # Show the 10 largest entries"));
+    assert!(stdout.contains("find . -type f -size +100M"));
+
+    let sessions = session_files(&root);
+    assert_eq!(sessions.len(), 1);
+    let session = fs::read_to_string(&sessions[0]).unwrap();
+    assert!(session.contains("find what is using disk space here"));
+    assert!(session.contains("du -sh"));
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = |path: &Path| fs::metadata(path).unwrap().permissions().mode() & 0o777;
+        assert_eq!(mode(&sessions[0]), 0o600);
+        assert_eq!(mode(sessions[0].parent().unwrap()), 0o700);
+    }
+}
+
+#[test]
+fn suggest_exec_needs_a_terminal() {
+    let root = app_root("suggest-exec");
+    let output = doum(
+        &root,
+        &["suggest", "--exec", "find what is using disk space here"],
+    );
+
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr(&output).contains("--exec needs an interactive terminal"));
+}
+
+#[test]
+fn auto_mode_selects_suggest() {
+    let root = app_root("auto-suggest");
+    let output = doum(&root, &["find what is using disk space here"]);
+
+    assert_eq!(output.status.code(), Some(0), "{}", stderr(&output));
+    assert!(stdout(&output).contains("du -sh * | sort -rh | head -n 10"));
+}
+
+#[test]
+fn auto_mode_selects_ask() {
+    let root = app_root("auto-ask");
+    let output = doum(
+        &root,
+        &["what is the difference between a hard link and a symbolic link"],
+    );
+
+    assert_eq!(output.status.code(), Some(0), "{}", stderr(&output));
+    assert!(stdout(&output).contains("A symbolic link points to a path by name"));
+}
+
+#[test]
+fn ask_streams_answer_and_saves_session() {
+    let root = app_root("ask");
+    let output = doum(
+        &root,
+        &[
+            "ask",
+            "what is the difference between a hard link and a symbolic link",
+        ],
+    );
+
+    assert_eq!(output.status.code(), Some(0), "{}", stderr(&output));
+    assert!(stdout(&output).contains("another name for the same inode"));
+    assert_eq!(session_files(&root).len(), 1);
+}
+
+#[test]
+fn explain_renders_tree() {
+    let root = app_root("explain");
+    let output = doum(&root, &["explain", "find src -name '*.rs' | xargs wc -l"]);
+
+    assert_eq!(output.status.code(), Some(0), "{}", stderr(&output));
+    let stdout = stdout(&output);
+    assert!(stdout.contains("├─ find src -name '*.rs'"));
+    assert!(stdout.contains("└─ | xargs wc -l"));
+    assert!(stdout.contains("No side effects"));
+}
+
+#[test]
+fn unrecorded_request_fails() {
+    let root = app_root("unrecorded");
+    let output = doum(&root, &["suggest", "a request that was never recorded"]);
+
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr(&output).contains("No recorded response for this request"));
+    assert!(session_files(&root).is_empty());
+}